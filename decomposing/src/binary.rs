@@ -0,0 +1,318 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::slice::aligned::Aligned;
+use crate::{DecomposingNormalizer, DecompositionData};
+
+/// сигнатура бинарного формата данных
+const MAGIC: [u8; 8] = *b"UDECOMP\0";
+/// версия бинарного формата
+const FORMAT_VERSION: u16 = 1;
+/// размер заголовка
+const HEADER_SIZE: usize = 32;
+/// размер контрольной суммы в конце данных
+const CHECKSUM_SIZE: usize = 4;
+
+// формат (little-endian):
+//  0 .. 8    сигнатура
+//  8 .. 10   версия формата
+//  10 .. 13  версия Unicode: major, minor, update
+//  13 .. 16  зарезервировано (нули)
+//  16 .. 20  continuous_block_end
+//  20 .. 24  длина index (количество u16)
+//  24 .. 28  длина data (количество u32)
+//  28 .. 32  длина expansions (количество u32)
+//  далее     index, выровненный нулями до 4 байт, data, expansions
+//  в конце   CRC-32 всех предшествующих байт
+
+/// версия Unicode, по которой подготовлены данные
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnicodeVersion
+{
+    pub major: u8,
+    pub minor: u8,
+    pub update: u8,
+}
+
+impl UnicodeVersion
+{
+    pub const fn new(major: u8, minor: u8, update: u8) -> Self
+    {
+        Self {
+            major,
+            minor,
+            update,
+        }
+    }
+}
+
+impl fmt::Display for UnicodeVersion
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.update)
+    }
+}
+
+/// заголовок бинарных данных
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataHeader
+{
+    /// версия бинарного формата
+    pub format_version: u16,
+    /// версия Unicode
+    pub unicode_version: UnicodeVersion,
+}
+
+/// ошибка загрузки бинарных данных
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataError
+{
+    /// данные короче заголовка или размеров таблиц, указанных в нём
+    Truncated,
+    /// данные длиннее размеров таблиц, указанных в заголовке
+    TrailingBytes,
+    /// неверная сигнатура
+    InvalidMagic,
+    /// неподдерживаемая версия бинарного формата
+    UnsupportedFormat(u16),
+    /// контрольная сумма не совпадает
+    ChecksumMismatch,
+    /// индекс указывает за пределы таблиц при поиске данных кодпоинта
+    IndexOutOfBounds(u32),
+    /// декомпозиция кодпоинта выходит за пределы expansions
+    ExpansionOutOfBounds(u32),
+    /// декомпозиция кодпоинта содержит некорректное скалярное значение, CCC или порядок
+    /// стартеров и нестартеров
+    InvalidMapping(u32),
+    /// первый уровень индекса не соответствует continuous_block_end
+    ContinuousBlockMismatch,
+    /// версия Unicode данных неизвестна - их нельзя записать в бинарном формате
+    UnknownUnicodeVersion,
+}
+
+impl fmt::Display for DataError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            DataError::Truncated => write!(f, "decomposition data is truncated"),
+            DataError::TrailingBytes => write!(f, "decomposition data has trailing bytes"),
+            DataError::InvalidMagic => write!(f, "not a decomposition data file"),
+            DataError::UnsupportedFormat(version) => {
+                write!(
+                    f,
+                    "unsupported decomposition data format version {}",
+                    version
+                )
+            }
+            DataError::ChecksumMismatch => write!(f, "decomposition data checksum mismatch"),
+            DataError::IndexOutOfBounds(code) => {
+                write!(f, "decomposition index out of bounds for U+{:04X}", code)
+            }
+            DataError::ExpansionOutOfBounds(code) => {
+                write!(
+                    f,
+                    "decomposition expansion out of bounds for U+{:04X}",
+                    code
+                )
+            }
+            DataError::InvalidMapping(code) => {
+                write!(f, "invalid decomposition mapping for U+{:04X}", code)
+            }
+            DataError::ContinuousBlockMismatch => {
+                write!(f, "continuous_block_end does not match decomposition index")
+            }
+            DataError::UnknownUnicodeVersion => {
+                write!(f, "unicode version of decomposition data is unknown")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DataError {}
+
+impl DataHeader
+{
+    /// прочитать заголовок бинарных данных без проверки таблиц
+    pub fn read(bytes: &[u8]) -> Result<Self, DataError>
+    {
+        if bytes.len() < HEADER_SIZE {
+            return Err(DataError::Truncated);
+        }
+
+        if bytes[0 .. 8] != MAGIC {
+            return Err(DataError::InvalidMagic);
+        }
+
+        let format_version = u16::from_le_bytes([bytes[8], bytes[9]]);
+
+        if format_version != FORMAT_VERSION {
+            return Err(DataError::UnsupportedFormat(format_version));
+        }
+
+        Ok(Self {
+            format_version,
+            unicode_version: UnicodeVersion::new(bytes[10], bytes[11], bytes[12]),
+        })
+    }
+}
+
+impl DecomposingNormalizer
+{
+    /// нормализатор из бинарных данных, записанных to_bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DataError>
+    {
+        let header = DataHeader::read(bytes)?;
+
+        let continuous_block_end = read_u32(bytes, 16);
+        let index_len = read_u32(bytes, 20) as u64;
+        let data_len = read_u32(bytes, 24) as u64;
+        let expansions_len = read_u32(bytes, 28) as u64;
+
+        // размеры считаем в u64: на 32-битных платформах они могут переполнить usize
+        let size = HEADER_SIZE as u64
+            + (index_len * 2).next_multiple_of(4)
+            + (data_len + expansions_len) * 4
+            + CHECKSUM_SIZE as u64;
+
+        if (bytes.len() as u64) < size {
+            return Err(DataError::Truncated);
+        }
+
+        if (bytes.len() as u64) > size {
+            return Err(DataError::TrailingBytes);
+        }
+
+        let index_len = index_len as usize;
+        let index_end = HEADER_SIZE + (index_len * 2).next_multiple_of(4);
+        let data_end = index_end + data_len as usize * 4;
+        let expansions_end = data_end + expansions_len as usize * 4;
+
+        if crc32(&bytes[.. expansions_end]) != read_u32(bytes, expansions_end) {
+            return Err(DataError::ChecksumMismatch);
+        }
+
+        // таблицы декодируются сразу в выровненные блоки памяти нормализатора и проверяются
+        // на месте
+
+        let normalizer = Self {
+            index: Aligned::from_fn(index_len, |i| read_u16(bytes, HEADER_SIZE + i * 2)),
+            data: Aligned::from_fn(data_len as usize, |i| read_u32(bytes, index_end + i * 4)),
+            expansions: Aligned::from_fn(expansions_len as usize, |i| {
+                read_u32(bytes, data_end + i * 4)
+            }),
+            continuous_block_end,
+            unicode_version: Some(header.unicode_version),
+        };
+
+        DecompositionData {
+            index: &normalizer.index,
+            data: &normalizer.data,
+            expansions: &normalizer.expansions,
+            continuous_block_end,
+        }
+        .validate()?;
+
+        Ok(normalizer)
+    }
+
+    /// записать таблицы нормализатора и их версию Unicode в бинарном формате. если версия
+    /// неизвестна - ошибка, её можно указать with_unicode_version
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DataError>
+    {
+        let unicode_version = self
+            .unicode_version
+            .ok_or(DataError::UnknownUnicodeVersion)?;
+
+        let index_size = (self.index.len() * 2).next_multiple_of(4);
+        let size = HEADER_SIZE
+            + index_size
+            + (self.data.len() + self.expansions.len()) * 4
+            + CHECKSUM_SIZE;
+
+        let mut bytes = Vec::with_capacity(size);
+
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&[
+            unicode_version.major,
+            unicode_version.minor,
+            unicode_version.update,
+            0,
+            0,
+            0,
+        ]);
+        bytes.extend_from_slice(&self.continuous_block_end.to_le_bytes());
+
+        for len in [self.index.len(), self.data.len(), self.expansions.len()] {
+            bytes.extend_from_slice(&(len as u32).to_le_bytes());
+        }
+
+        for &entry in self.index.iter() {
+            bytes.extend_from_slice(&entry.to_le_bytes());
+        }
+
+        bytes.resize(HEADER_SIZE + index_size, 0);
+
+        for &entry in self.data.iter().chain(self.expansions.iter()) {
+            bytes.extend_from_slice(&entry.to_le_bytes());
+        }
+
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+
+        Ok(bytes)
+    }
+}
+
+/// прочитать u32 по смещению
+#[inline(always)]
+fn read_u32(bytes: &[u8], offset: usize) -> u32
+{
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// прочитать u16 по смещению
+#[inline(always)]
+fn read_u16(bytes: &[u8], offset: usize) -> u16
+{
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// таблица CRC-32 (полином 0xEDB88320)
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xEDB88320,
+                _ => crc >> 1,
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+};
+
+/// CRC-32 (IEEE 802.3)
+fn crc32(bytes: &[u8]) -> u32
+{
+    !bytes.iter().fold(!0, |crc, &byte| {
+        CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
@@ -0,0 +1,152 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::codepoint::Codepoint;
+use crate::slice::iter::CharsIter;
+use crate::{flush, write_str, DecomposingNormalizer};
+
+/// ошибка нормализации: исходные данные не являются корректной последовательностью UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utf8NormalizeError
+{
+    /// смещение первого байта некорректной последовательности
+    offset: usize,
+}
+
+impl Utf8NormalizeError
+{
+    pub(crate) fn new(offset: usize) -> Self
+    {
+        Self { offset }
+    }
+
+    /// смещение первого байта некорректной последовательности
+    pub fn offset(&self) -> usize
+    {
+        self.offset
+    }
+}
+
+impl fmt::Display for Utf8NormalizeError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "invalid UTF-8 sequence at offset {}", self.offset)
+    }
+}
+
+impl core::error::Error for Utf8NormalizeError {}
+
+impl DecomposingNormalizer
+{
+    /// нормализация байтов UTF-8 с проверкой их корректности
+    #[inline(never)]
+    pub fn normalize_bytes(&self, input: &[u8]) -> Result<String, Utf8NormalizeError>
+    {
+        let mut result = String::with_capacity(input.len());
+        let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+
+        self.normalize_bytes_inline(input, &mut result, &mut buffer, false, true)?;
+
+        Ok(result)
+    }
+
+    /// нормализация байтов UTF-8, некорректные последовательности заменяются на U+FFFD
+    #[inline(never)]
+    pub fn normalize_bytes_lossy(&self, input: &[u8]) -> String
+    {
+        let mut result = String::with_capacity(input.len());
+        let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+
+        let _ = self.normalize_bytes_inline(input, &mut result, &mut buffer, true, true);
+
+        result
+    }
+
+    /// цикл нормализации с проверкой UTF-8: если буфер нестартеров не пуст, то брейкпоинт
+    /// находится сразу за последним обработанным символом
+    ///
+    /// last - данные завершают поток: буфер нестартеров сливается в результат. иначе буфер
+    /// сохраняется до следующего фрагмента, а последовательность, прерванная концом данных,
+    /// не является ошибкой - возвращается её длина
+    #[inline(always)]
+    pub(crate) fn normalize_bytes_inline(
+        &self,
+        input: &[u8],
+        result: &mut String,
+        buffer: &mut Vec<Codepoint>,
+        lossy: bool,
+        last: bool,
+    ) -> Result<usize, Utf8NormalizeError>
+    {
+        let iter = &mut CharsIter::from_bytes(input);
+
+        loop {
+            if iter.is_empty() {
+                if last {
+                    flush(result, buffer);
+                }
+
+                write_str(result, iter.ending_slice());
+
+                return Ok(0);
+            }
+
+            let first = unsafe { iter.next_unchecked() };
+
+            // в отличие от валидной строки, байты 0x80 ..= 0xC1 здесь - ошибка,
+            // пропускаем только ASCII
+
+            if first < 0x80 {
+                if !buffer.is_empty() {
+                    flush(result, buffer);
+                }
+
+                continue;
+            }
+
+            let code = match iter.next_nonascii_bytes_checked(first) {
+                Ok(code) => code,
+                Err(width) => {
+                    if !last && iter.is_empty() && (0xC2 ..= 0xF4).contains(&first) {
+                        write_str(result, iter.block_slice(width));
+                        return Ok(width as usize);
+                    }
+
+                    if !lossy {
+                        return Err(Utf8NormalizeError {
+                            offset: input.len() - iter.remaining() - width as usize,
+                        });
+                    }
+
+                    flush(result, buffer);
+                    write_str(result, iter.block_slice(width));
+                    result.push(char::REPLACEMENT_CHARACTER);
+                    iter.set_breakpoint();
+
+                    continue;
+                }
+            };
+
+            let dec_value = self.get_decomposition_value(code);
+
+            if (dec_value as u8 >> 2) == 0 {
+                if !buffer.is_empty() {
+                    flush(result, buffer);
+                }
+
+                continue;
+            }
+
+            let width = [2, 2, 3, 4][((first >> 4) & 3) as usize];
+
+            if !iter.at_breakpoint(width) {
+                write_str(result, iter.block_slice(width));
+            }
+
+            self.handle_decomposition_value(dec_value, code, result, buffer);
+            iter.set_breakpoint();
+        }
+    }
+}
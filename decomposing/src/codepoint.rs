@@ -1,5 +1,32 @@
-/// кодпоинт для декомпозиции в виде u32, где CCC хранится в старших битах
-#[derive(Debug, Clone, Copy)]
+use alloc::vec::Vec;
+
+/// значения CCC, встречающиеся в Unicode, по возрастанию: в данных CCC хранится как индекс
+/// в этом списке, что сохраняет порядок сортировки
+pub(crate) const CCC_VALUES: [u8; 56] = [
+    0, 1, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28,
+    29, 30, 31, 32, 33, 34, 35, 36, 84, 91, 103, 107, 118, 122, 129, 130, 132, 202, 214, 216, 218,
+    220, 222, 224, 226, 228, 230, 232, 233, 234, 240,
+];
+
+/// нестартеры с канонической декомпозицией (Unicode 15.1): в данных нормализатора для них
+/// записана декомпозиция, а не CCC, поэтому CCC берётся из первого кодпоинта декомпозиции.
+/// остальные символы, декомпозиция которых начинается с нестартера, - стартеры
+pub(crate) const DECOMPOSING_NONSTARTERS: [u32; 4] = [0x0340, 0x0341, 0x0343, 0x0344];
+
+/// ёмкость буфера нестартеров на стеке: стартер и 30 нестартеров Stream-Safe Text Format
+/// с запасом на декомпозицию, добавляемую в буфер целиком
+const INLINE_CAPACITY: usize = 64;
+/// наибольшее количество кодпоинтов, добавляемых в буфер одной декомпозицией (поле
+/// количества в данных - 5 бит)
+const MAX_DECOMPOSITION_LEN: usize = 32;
+
+/// кодпоинт результата декомпозиции вместе с его CCC, упакованные в u32:
+///  - биты 8 .. 29 - код символа
+///  - биты 0 .. 7 - упакованный CCC: индекс класса в списке классов, встречающихся в Unicode,
+///    по возрастанию. порядок упакованных значений совпадает с порядком CCC, 0 - стартер
+///
+/// значение по умолчанию - стартер U+0000, его можно использовать для заполнения буфера
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub struct Codepoint(u32);
 
 impl From<Codepoint> for char
@@ -13,45 +40,179 @@ impl From<Codepoint> for char
 
 impl Codepoint
 {
+    /// кодпоинт из символа и его CCC; None, если такой класс не встречается в Unicode
+    pub fn new(c: char, ccc: u8) -> Option<Self>
+    {
+        CCC_VALUES
+            .binary_search(&ccc)
+            .ok()
+            .map(|packed| Self::from_code_and_ccc(c as u32, packed as u8))
+    }
+
+    /// код символа
     #[inline(always)]
     pub fn code(&self) -> u32
     {
         self.0 >> 8
     }
 
+    /// символ
+    #[inline(always)]
+    pub fn char(&self) -> char
+    {
+        char::from(*self)
+    }
+
+    /// класс канонического комбинирования (Canonical_Combining_Class)
     #[inline(always)]
     pub fn ccc(&self) -> u8
+    {
+        CCC_VALUES[self.packed_ccc() as usize]
+    }
+
+    /// упакованный CCC: сравнивается так же, как CCC, и используется для сортировки
+    #[inline(always)]
+    pub fn packed_ccc(&self) -> u8
     {
         self.0 as u8
     }
 
+    /// упакованное значение
+    #[inline(always)]
+    pub fn to_packed(&self) -> u32
+    {
+        self.0
+    }
+
+    /// стартер (CCC = 0)?
     #[inline(always)]
     pub fn is_starter(&self) -> bool
     {
         self.0 as u8 == 0
     }
 
+    /// нестартер (CCC > 0)?
     #[inline(always)]
     pub fn is_nonstarter(&self) -> bool
     {
         self.0 as u8 != 0
     }
 
+    /// кодпоинт из упакованного значения
     #[inline(always)]
-    pub fn from_baked(code: u32) -> Self
+    pub(crate) fn from_baked(code: u32) -> Self
     {
         Self(code)
     }
 
+    /// кодпоинт из кода символа и упакованного CCC
     #[inline(always)]
-    pub fn from_code_and_ccc(code: u32, ccc: u8) -> Self
+    pub(crate) fn from_code_and_ccc(code: u32, ccc: u8) -> Self
     {
         Self(code << 8 | (ccc as u32))
     }
 
+    /// стартер
     #[inline(always)]
-    pub fn from_code(code: u32) -> Self
+    pub(crate) fn from_code(code: u32) -> Self
     {
         Self(code << 8)
     }
 }
+
+/// приёмник кодпоинтов декомпозиции
+pub(crate) trait CodepointBuffer
+{
+    fn push(&mut self, codepoint: Codepoint);
+}
+
+impl CodepointBuffer for Vec<Codepoint>
+{
+    #[inline(always)]
+    fn push(&mut self, codepoint: Codepoint)
+    {
+        Vec::push(self, codepoint)
+    }
+}
+
+/// буфер нестартеров нормализации
+pub(crate) trait NonstarterBuffer: CodepointBuffer
+{
+    /// кодпоинты буфера
+    fn codepoints(&mut self) -> &mut [Codepoint];
+
+    /// очистить буфер
+    fn clear(&mut self);
+
+    /// буфер может не вместить следующую декомпозицию - его нужно слить заранее
+    #[inline(always)]
+    fn is_full(&self) -> bool
+    {
+        false
+    }
+}
+
+impl NonstarterBuffer for Vec<Codepoint>
+{
+    #[inline(always)]
+    fn codepoints(&mut self) -> &mut [Codepoint]
+    {
+        self
+    }
+
+    #[inline(always)]
+    fn clear(&mut self)
+    {
+        Vec::clear(self)
+    }
+}
+
+/// буфер нестартеров на стеке
+pub(crate) struct InlineBuffer
+{
+    pub codepoints: [Codepoint; INLINE_CAPACITY],
+    pub len: usize,
+}
+
+impl InlineBuffer
+{
+    #[inline(always)]
+    pub fn new() -> Self
+    {
+        Self {
+            codepoints: [Codepoint::default(); INLINE_CAPACITY],
+            len: 0,
+        }
+    }
+}
+
+impl CodepointBuffer for InlineBuffer
+{
+    #[inline(always)]
+    fn push(&mut self, codepoint: Codepoint)
+    {
+        self.codepoints[self.len] = codepoint;
+        self.len += 1;
+    }
+}
+
+impl NonstarterBuffer for InlineBuffer
+{
+    #[inline(always)]
+    fn codepoints(&mut self) -> &mut [Codepoint]
+    {
+        &mut self.codepoints[.. self.len]
+    }
+
+    #[inline(always)]
+    fn clear(&mut self)
+    {
+        self.len = 0;
+    }
+
+    #[inline(always)]
+    fn is_full(&self) -> bool
+    {
+        self.len > INLINE_CAPACITY - MAX_DECOMPOSITION_LEN
+    }
+}
@@ -0,0 +1,88 @@
+use alloc::vec::Vec;
+
+use crate::codepoint::{Codepoint, CodepointBuffer};
+use crate::slice::iter::CharsIter;
+use crate::{sort_nonstarters, DecomposingNormalizer, NeedMore};
+
+impl DecomposingNormalizer
+{
+    /// нормализация строки, результат - кодпоинты вместе с их CCC
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize_to_codepoints(&self, input: &str) -> Vec<Codepoint>
+    {
+        let mut result = Vec::with_capacity(input.len());
+
+        self.decompose_codepoints(input, &mut result);
+        sort_nonstarters(&mut result);
+
+        result
+    }
+
+    /// нормализация строки в срез кодпоинтов, результат - количество записанных кодпоинтов.
+    /// если результат не помещается в срез - ошибка с необходимой длиной среза, содержимое
+    /// среза при этом не определено. буфер можно заполнить значением Codepoint::default()
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize_to_codepoints_slice(
+        &self,
+        input: &str,
+        output: &mut [Codepoint],
+    ) -> Result<usize, NeedMore>
+    {
+        let mut buffer = SliceBuffer { output, len: 0 };
+
+        self.decompose_codepoints(input, &mut buffer);
+
+        let len = buffer.len;
+
+        if len > buffer.output.len() {
+            return Err(NeedMore { required: len });
+        }
+
+        sort_nonstarters(&mut buffer.output[.. len]);
+
+        Ok(len)
+    }
+
+    /// декомпозиция строки без сортировки нестартеров
+    #[inline(always)]
+    fn decompose_codepoints(&self, input: &str, buffer: &mut impl CodepointBuffer)
+    {
+        let iter = &mut CharsIter::new(input);
+
+        while !iter.is_empty() {
+            let first = unsafe { iter.next_unchecked() };
+
+            if first < 0xC2 {
+                buffer.push(Codepoint::from_code(first as u32));
+                continue;
+            }
+
+            let code = unsafe { iter.next_nonascii_bytes_unchecked(first) };
+            let value = self.get_decomposition_value(code);
+
+            self.decompose_to_buffer(value, code, buffer);
+        }
+    }
+}
+
+/// запись кодпоинтов в срез: кодпоинты, не поместившиеся в срез, только подсчитываются
+struct SliceBuffer<'a>
+{
+    output: &'a mut [Codepoint],
+    len: usize,
+}
+
+impl<'a> CodepointBuffer for SliceBuffer<'a>
+{
+    #[inline(always)]
+    fn push(&mut self, codepoint: Codepoint)
+    {
+        if let Some(slot) = self.output.get_mut(self.len) {
+            *slot = codepoint;
+        }
+
+        self.len += 1;
+    }
+}
@@ -0,0 +1,413 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::codepoint::Codepoint;
+use crate::hangul::compose_hangul;
+use crate::slice::iter::CharsIter;
+use crate::{sort_nonstarters, write, write_str, DecomposingNormalizer, IsNormalized};
+use crate::{
+    LAST_DECOMPOSING_CODEPOINT_BLOCK, MARKER_COMBINES_BACKWARDS, MARKER_HANGUL, MARKER_NONSTARTER,
+    MARKER_STARTER,
+};
+
+/// нормализатор NF(K)C. пары канонической композиции восстанавливаются по встроенным таблицам
+/// NFD один раз, при первой композиции, и общие для всех нормализаторов
+#[derive(Clone)]
+pub struct ComposingNormalizer
+{
+    /// нормализатор NF(K)D, по его данным раскладываем символы перед композицией
+    decomposing: DecomposingNormalizer,
+}
+
+impl ComposingNormalizer
+{
+    /// нормализация строки
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize(&self, input: &str) -> String
+    {
+        let mut result = String::with_capacity(input.len());
+        let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+        let iter = &mut CharsIter::new(input);
+
+        loop {
+            let entry = match !buffer.is_empty() {
+                true => match self.forward(iter, &mut result, &mut buffer) {
+                    Some(entry) => Some(entry),
+                    None => continue,
+                },
+                false => self.fast_forward(iter, &mut result, &mut buffer),
+            };
+
+            match entry {
+                Some((dec_value, code)) => {
+                    decompose(&self.decomposing, dec_value, code, &mut buffer);
+                    iter.set_breakpoint();
+                }
+                None => return result,
+            }
+        }
+    }
+
+    /// строка находится в форме NF(K)C?
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn is_normalized(&self, input: &str) -> bool
+    {
+        match self.quick_check(input) {
+            IsNormalized::Yes => true,
+            IsNormalized::No => false,
+            IsNormalized::Maybe => self.normalize(input) == input,
+        }
+    }
+
+    /// быстрая проверка NFC_QC / NFKC_QC с учётом порядка нестартеров по CCC
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn quick_check(&self, input: &str) -> IsNormalized
+    {
+        let iter = &mut CharsIter::new(input);
+        let mut last_ccc = 0;
+        let mut result = IsNormalized::Yes;
+
+        loop {
+            if iter.is_empty() {
+                return result;
+            }
+
+            let first = unsafe { iter.next_unchecked() };
+
+            if first < 0xC2 {
+                last_ccc = 0;
+                continue;
+            }
+
+            let code = unsafe { iter.next_nonascii_bytes_unchecked(first) };
+            let dec_value = self.decomposing.get_decomposition_value(code);
+
+            if is_composition_boundary(dec_value) {
+                last_ccc = 0;
+                continue;
+            }
+
+            let marker = (dec_value as u8) >> 1;
+
+            let ccc = match marker == MARKER_NONSTARTER {
+                true => (dec_value >> 8) as u8,
+                false => 0,
+            };
+
+            if ccc != 0 && ccc < last_ccc {
+                return IsNormalized::No;
+            }
+
+            last_ccc = ccc;
+
+            // флаг "быстрая проверка - не Yes": символы, которые могут скомбинироваться с
+            // предыдущими - Maybe, символы с декомпозицией, исключённые из композиции - No
+
+            if dec_value & 1 != 0 {
+                match marker {
+                    MARKER_STARTER | MARKER_COMBINES_BACKWARDS | MARKER_NONSTARTER => {
+                        result = IsNormalized::Maybe
+                    }
+                    _ => return IsNormalized::No,
+                }
+            }
+        }
+    }
+
+    /// если буфер не пуст, мы не можем перейти к быстрой проверке.
+    /// прочитаем следующий кодпоинт, и если он не комбинируется с предыдущими - скомбинируем буфер
+    #[inline(always)]
+    fn forward(
+        &self,
+        iter: &mut CharsIter,
+        result: &mut String,
+        buffer: &mut Vec<Codepoint>,
+    ) -> Option<(u32, u32)>
+    {
+        iter.set_breakpoint();
+
+        if !iter.is_empty() {
+            let first = unsafe { iter.next_unchecked() };
+
+            if first >= 0xC2 {
+                let code = unsafe { iter.next_nonascii_bytes_unchecked(first) };
+                let dec_value = self.decomposing.get_decomposition_value(code);
+
+                if !is_composition_boundary(dec_value) {
+                    return Some((dec_value, code));
+                }
+            }
+        }
+
+        self.flush(result, buffer);
+        None
+    }
+
+    /// цикл быстрой проверки, является-ли часть строки уже нормализованной
+    #[inline(always)]
+    fn fast_forward(
+        &self,
+        iter: &mut CharsIter,
+        result: &mut String,
+        buffer: &mut Vec<Codepoint>,
+    ) -> Option<(u32, u32)>
+    {
+        loop {
+            if iter.is_empty() {
+                write_str(result, iter.ending_slice());
+                return None;
+            }
+
+            let first = unsafe { iter.next_unchecked() };
+
+            // ASCII не имеет декомпозиции и не комбинируется с предыдущими символами, байты
+            // продолжения последовательностей пропускаются так же, как и при декомпозиции
+
+            if first < 0xC2 {
+                continue;
+            }
+
+            let code = unsafe { iter.next_nonascii_bytes_unchecked(first) };
+            let dec_value = self.decomposing.get_decomposition_value(code);
+
+            if is_composition_boundary(dec_value) {
+                continue;
+            }
+
+            let width = [2, 2, 3, 4][((first >> 4) & 3) as usize];
+
+            // предыдущий символ - стартер, который может скомбинироваться с текущим:
+            //  - сливаем отрезок от брейкпоинта до этого стартера
+            //  - сам стартер переносим в буфер
+
+            if !iter.at_breakpoint(width) {
+                let (previous_width, previous) = unsafe { iter.previous_char(width) };
+
+                write_str(result, iter.block_slice(width + previous_width));
+                buffer.push(Codepoint::from_code(previous));
+            }
+
+            return Some((dec_value, code));
+        }
+    }
+
+    /// отсортировать нестартеры буфера по CCC, скомбинировать кодпоинты, записать в результат
+    /// и освободить буфер
+    #[inline(never)]
+    fn flush(&self, result: &mut String, buffer: &mut Vec<Codepoint>)
+    {
+        sort_nonstarters(buffer);
+        self.compose(buffer);
+
+        for &codepoint in buffer.iter() {
+            write(result, codepoint);
+        }
+
+        buffer.clear();
+    }
+
+    /// каноническая композиция упорядоченной последовательности кодпоинтов
+    #[inline(always)]
+    fn compose(&self, buffer: &mut Vec<Codepoint>)
+    {
+        let compositions = shared_compositions();
+
+        // позиция последнего стартера и CCC последнего оставшегося после него кодпоинта
+        let mut starter: Option<usize> = None;
+        let mut last_ccc: Option<u8> = None;
+        let mut length = 0;
+
+        for i in 0 .. buffer.len() {
+            let codepoint = buffer[i];
+            let ccc = codepoint.packed_ccc();
+
+            if let Some(starter) = starter {
+                let blocked = match last_ccc {
+                    Some(last_ccc) => last_ccc == 0 || last_ccc >= ccc,
+                    None => false,
+                };
+
+                if !blocked {
+                    if let Some(composite) =
+                        combine(compositions, buffer[starter].code(), codepoint.code())
+                    {
+                        buffer[starter] = Codepoint::from_code(composite);
+                        continue;
+                    }
+                }
+            }
+
+            match ccc == 0 {
+                true => {
+                    starter = Some(length);
+                    last_ccc = None;
+                }
+                false => last_ccc = Some(ccc),
+            }
+
+            buffer[length] = codepoint;
+            length += 1;
+        }
+
+        buffer.truncate(length);
+    }
+
+    /// NFC-нормализатор на статических данных:
+    /// `static NFC: ComposingNormalizer = ComposingNormalizer::new_nfc();`
+    pub const fn new_nfc() -> Self
+    {
+        Self {
+            decomposing: DecomposingNormalizer::new_nfd(),
+        }
+    }
+
+    /// NFKC-нормализатор на статических данных
+    pub const fn new_nfkc() -> Self
+    {
+        Self {
+            decomposing: DecomposingNormalizer::new_nfkd(),
+        }
+    }
+
+    /// общий для всего процесса NFC-нормализатор
+    #[inline(always)]
+    pub fn nfc() -> &'static Self
+    {
+        static NFC: ComposingNormalizer = ComposingNormalizer::new_nfc();
+
+        &NFC
+    }
+
+    /// общий для всего процесса NFKC-нормализатор
+    #[inline(always)]
+    pub fn nfkc() -> &'static Self
+    {
+        static NFKC: ComposingNormalizer = ComposingNormalizer::new_nfkc();
+
+        &NFKC
+    }
+}
+
+/// первичный композит пары кодпоинтов
+#[inline(always)]
+fn combine(compositions: &[u64], first: u32, second: u32) -> Option<u32>
+{
+    if let Some(composite) = compose_hangul(first, second) {
+        return Some(composite);
+    }
+
+    let pair = (first as u64) << 21 | second as u64;
+
+    compositions
+        .binary_search_by_key(&pair, |&entry| entry >> 21)
+        .ok()
+        .map(|index| (compositions[index] & 0x1FFFFF) as u32)
+}
+
+/// символ - стартер без декомпозиции, который не комбинируется с предыдущими (младший бит -
+/// флаг "быстрая проверка NF(K)C - не Yes"), или слог хангыль
+#[inline(always)]
+fn is_composition_boundary(dec_value: u32) -> bool
+{
+    dec_value as u8 == 0 || dec_value as u8 == MARKER_HANGUL << 1
+}
+
+/// записать в буфер полную декомпозицию кодпоинта, не сортируя её; слоги хангыль не раскладываем,
+/// т.к. LV + T комбинируются так же, как и L + V
+#[inline(always)]
+fn decompose(normalizer: &DecomposingNormalizer, value: u32, code: u32, buffer: &mut Vec<Codepoint>)
+{
+    match (value as u8) >> 1 == MARKER_HANGUL {
+        true => buffer.push(Codepoint::from_code(code)),
+        false => normalizer.decompose_to_buffer(value, code, buffer),
+    }
+}
+
+/// пары канонической композиции встроенных таблиц: первый кодпоинт, второй кодпоинт и
+/// результат - по 21 биту, отсортированы по паре исходных кодпоинтов. таблица строится при
+/// первом обращении; если несколько потоков построили её одновременно, лишние копии освобождаются
+#[inline(always)]
+fn shared_compositions() -> &'static [u64]
+{
+    static COMPOSITIONS: AtomicPtr<Vec<u64>> = AtomicPtr::new(ptr::null_mut());
+
+    let current = COMPOSITIONS.load(Ordering::Acquire);
+
+    if !current.is_null() {
+        return unsafe { &*current };
+    }
+
+    let table = Box::into_raw(Box::new(compositions(DecomposingNormalizer::nfd())));
+
+    match COMPOSITIONS.compare_exchange(ptr::null_mut(), table, Ordering::AcqRel, Ordering::Acquire)
+    {
+        Ok(_) => unsafe { &*table },
+        Err(current) => {
+            drop(unsafe { Box::from_raw(table) });
+            unsafe { &*current }
+        }
+    }
+}
+
+/// пары канонической композиции, восстановленные по данным NFD-декомпозиции:
+/// первичный композит - символ с канонической декомпозицией, не исключённый из композиции
+/// (младший бит значения - флаг "быстрая проверка NFC - не Yes")
+fn compositions(nfd: &DecomposingNormalizer) -> Vec<u64>
+{
+    let last_code = ((LAST_DECOMPOSING_CODEPOINT_BLOCK as u32) + 1) << 7;
+
+    // полная декомпозиция -> первичный композит
+    let mut composites: BTreeMap<Vec<u32>, u32> = BTreeMap::new();
+    let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+
+    for code in 0 .. last_code {
+        let value = nfd.get_decomposition_value(code);
+
+        match (value as u8) >> 1 {
+            MARKER_STARTER | MARKER_COMBINES_BACKWARDS | MARKER_NONSTARTER | MARKER_HANGUL => {
+                continue
+            }
+            _ if value & 1 != 0 => continue,
+            _ => (),
+        }
+
+        buffer.clear();
+        decompose(nfd, value, code, &mut buffer);
+
+        if buffer.len() < 2 || buffer[0].is_nonstarter() {
+            continue;
+        }
+
+        composites.insert(
+            buffer.iter().map(|codepoint| codepoint.code()).collect(),
+            code,
+        );
+    }
+
+    // каноническая декомпозиция первичного композита - пара, первый элемент которой
+    // раскладывается в полную декомпозицию композита без последнего кодпоинта
+
+    let mut compositions: Vec<u64> = composites
+        .iter()
+        .filter_map(|(decomposition, &composite)| {
+            let (&second, init) = decomposition.split_last()?;
+
+            let first = match init.len() {
+                1 => init[0],
+                _ => *composites.get(init)?,
+            };
+
+            Some((first as u64) << 42 | (second as u64) << 21 | composite as u64)
+        })
+        .collect();
+
+    compositions.sort_unstable();
+    compositions
+}
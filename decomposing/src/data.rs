@@ -1,3 +1,13 @@
+use crate::binary::{DataError, UnicodeVersion};
+use crate::codepoint::CCC_VALUES;
+use crate::decomposed::CAPACITY;
+use crate::slice::aligned::AlignedArray;
+use crate::{
+    LAST_DECOMPOSING_CODEPOINT_BLOCK, MARKER_COMBINES_BACKWARDS, MARKER_EXPANSION,
+    MARKER_EXPANSION_COMBINED_EMPTY, MARKER_EXPANSION_COMBINED_PATCH, MARKER_HANGUL,
+    MARKER_NONSTARTER, MARKER_SINGLETON, MARKER_STARTER,
+};
+
 /// структура хранимых данных для нормализации
 pub struct DecompositionData<'a>
 {
@@ -11,14 +21,197 @@ pub struct DecompositionData<'a>
     pub continuous_block_end: u32,
 }
 
+impl<'a> DecompositionData<'a>
+{
+    /// проверка структуры данных: все обращения нормализатора к таблицам не выходят за их
+    /// пределы, а декомпозиции содержат корректные скалярные значения и CCC
+    pub fn validate(&self) -> Result<(), DataError>
+    {
+        // блоки до continuous_block_end адресуются без первого уровня индекса - он должен
+        // указывать на те же блоки
+
+        let continuous_groups =
+            (self.continuous_block_end >> 7).min(LAST_DECOMPOSING_CODEPOINT_BLOCK as u32);
+
+        for group in 0 ..= continuous_groups {
+            if self.index.get(group as usize) != Some(&(0x600 | (group << 4) as u16)) {
+                return Err(DataError::ContinuousBlockMismatch);
+            }
+        }
+
+        for code in 0 .. 0x110000 {
+            let value = self.value(code).ok_or(DataError::IndexOutOfBounds(code))?;
+            self.validate_value(code, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// данные о декомпозиции символа (как DecomposingNormalizer::get_decomposition_value),
+    /// None - выход за пределы таблиц
+    fn value(&self, code: u32) -> Option<u32>
+    {
+        let data_block_base = match code <= self.continuous_block_end {
+            true => 0x600 | (((code >> 3) as u16) & !0xF),
+            false => {
+                let group_index = (code >> 7) as u16;
+
+                if group_index > LAST_DECOMPOSING_CODEPOINT_BLOCK {
+                    return Some(0);
+                };
+
+                *self.index.get(group_index as usize)?
+            }
+        };
+
+        let code_offsets = (code as u16) & 0x7F;
+        let data_block_index = data_block_base | (code_offsets >> 3);
+        let index = *self.index.get(data_block_index as usize)? | code_offsets & 0x7;
+
+        self.data.get(index as usize).copied()
+    }
+
+    /// проверка данных о декомпозиции символа
+    fn validate_value(&self, code: u32, value: u32) -> Result<(), DataError>
+    {
+        let invalid = DataError::InvalidMapping(code);
+
+        match (value as u8) >> 1 {
+            MARKER_STARTER | MARKER_COMBINES_BACKWARDS => Ok(()),
+            MARKER_NONSTARTER => match is_nonstarter_value(value) {
+                true => Ok(()),
+                false => Err(invalid),
+            },
+            MARKER_SINGLETON => match char::from_u32(value >> 8).is_some() {
+                true => Ok(()),
+                false => Err(invalid),
+            },
+            MARKER_EXPANSION => self.validate_expansion(code, value, 0),
+            MARKER_EXPANSION_COMBINED_PATCH | MARKER_EXPANSION_COMBINED_EMPTY => {
+                self.validate_expansion(code, value, 1)
+            }
+            MARKER_HANGUL => match (0xAC00 ..= 0xD7A3).contains(&code) {
+                true => Ok(()),
+                false => Err(invalid),
+            },
+            _ => {
+                // пара: первый кодпоинт записывается в результат как есть, CCC второго
+                // берётся из его данных
+                let c1 = (value as u16 >> 1) as u32;
+                let c2 = value >> 16;
+
+                let valid = [c1, c2].iter().all(|&c| char::from_u32(c).is_some())
+                    && self.value(c1).is_some_and(is_starter_value)
+                    && self
+                        .value(c2)
+                        .is_some_and(|v| is_starter_value(v) || is_nonstarter_value(v));
+
+                match valid {
+                    true => Ok(()),
+                    false => Err(invalid),
+                }
+            }
+        }
+    }
+
+    /// проверка декомпозиции, вынесенной во внешний блок: last_starter - позиция последнего
+    /// стартера, за ним следуют только нестартеры; декомпозиция, начинающаяся с нестартера,
+    /// стартеров не содержит
+    fn validate_expansion(&self, code: u32, value: u32, shift: u32) -> Result<(), DataError>
+    {
+        let last_starter = ((value >> 8) & 0x1F) as usize;
+        let count = ((value >> 13) & 0x1F) as usize;
+        let index = ((value >> 18) + shift) as usize;
+
+        let entries = match self.expansions.get(index .. index + count) {
+            Some(entries) if last_starter < count => entries,
+            _ => return Err(DataError::ExpansionOutOfBounds(code)),
+        };
+
+        // полная декомпозиция символа должна помещаться в Decomposed
+
+        if count > CAPACITY {
+            return Err(DataError::InvalidMapping(code));
+        }
+
+        let valid = entries
+            .iter()
+            .all(|&entry| char::from_u32(entry >> 8).is_some() && is_valid_ccc(entry as u8))
+            && entries[last_starter + 1 ..]
+                .iter()
+                .all(|&entry| entry as u8 != 0)
+            && match entries[0] as u8 == 0 {
+                true => entries[last_starter] as u8 == 0,
+                false => last_starter == 0,
+            };
+
+        match valid {
+            true => Ok(()),
+            false => Err(DataError::InvalidMapping(code)),
+        }
+    }
+}
+
+/// данные стартера без декомпозиции
+#[inline(always)]
+fn is_starter_value(value: u32) -> bool
+{
+    (value as u8 >> 2) == 0 && (value >> 8) as u8 == 0
+}
+
+/// данные нестартера без декомпозиции с корректным CCC
+#[inline(always)]
+fn is_nonstarter_value(value: u32) -> bool
+{
+    let ccc = (value >> 8) as u8;
+
+    (value as u8) >> 1 == MARKER_NONSTARTER && ccc != 0 && is_valid_ccc(ccc)
+}
+
+/// упакованный CCC соответствует классу, встречающемуся в Unicode?
+#[inline(always)]
+fn is_valid_ccc(ccc: u8) -> bool
+{
+    (ccc as usize) < CCC_VALUES.len()
+}
+
+/// данные для NFD-нормализации в том виде, в котором они запечены; в программу попадают
+/// только их выровненные копии, сделанные на этапе компиляции
+const NFD: DecompositionData<'static> = include!("./../../data/nfd.txt");
+/// данные для NFKD-нормализации в том виде, в котором они запечены
+const NFKD: DecompositionData<'static> = include!("./../../data/nfkd.txt");
+
+static NFD_INDEX: AlignedArray<u16, { NFD.index.len() }> = AlignedArray::from_slice(NFD.index);
+static NFD_DATA: AlignedArray<u32, { NFD.data.len() }> = AlignedArray::from_slice(NFD.data);
+static NFD_EXPANSIONS: AlignedArray<u32, { NFD.expansions.len() }> =
+    AlignedArray::from_slice(NFD.expansions);
+
+static NFKD_INDEX: AlignedArray<u16, { NFKD.index.len() }> = AlignedArray::from_slice(NFKD.index);
+static NFKD_DATA: AlignedArray<u32, { NFKD.data.len() }> = AlignedArray::from_slice(NFKD.data);
+static NFKD_EXPANSIONS: AlignedArray<u32, { NFKD.expansions.len() }> =
+    AlignedArray::from_slice(NFKD.expansions);
+
+/// версия Unicode, по которой подготовлены встроенные таблицы NFD и NFKD
+pub const UNICODE_VERSION: UnicodeVersion = UnicodeVersion::new(15, 1, 0);
+
 /// данные для NFD-нормализации
-pub fn nfd<'a>() -> DecompositionData<'a>
+pub const fn nfd() -> DecompositionData<'static>
 {
-    include!("./../../data/nfd.txt")
+    DecompositionData {
+        index: &NFD_INDEX.0,
+        data: &NFD_DATA.0,
+        expansions: &NFD_EXPANSIONS.0,
+        continuous_block_end: NFD.continuous_block_end,
+    }
 }
 
 /// данные для NFKD-нормализации
-pub fn nfkd<'a>() -> DecompositionData<'a>
+pub const fn nfkd() -> DecompositionData<'static>
 {
-    include!("./../../data/nfkd.txt")
+    DecompositionData {
+        index: &NFKD_INDEX.0,
+        data: &NFKD_DATA.0,
+        expansions: &NFKD_EXPANSIONS.0,
+        continuous_block_end: NFKD.continuous_block_end,
+    }
 }
@@ -0,0 +1,323 @@
+use alloc::vec::Vec;
+
+use crate::codepoint::Codepoint;
+use crate::slice::iter::CharsIter;
+use crate::{sort_nonstarters, DecomposingNormalizer};
+
+/// ленивая NF(K)D нормализация строки: итератор символов результата
+///
+/// строка разбивается на сегменты - стартер и следующие за ним нестартеры; декомпозиция
+/// сегмента сортируется по CCC и отдаётся посимвольно - как с начала, так и с конца строки
+pub struct DecomposeIter<'a>
+{
+    /// нормализатор
+    normalizer: &'a DecomposingNormalizer,
+    /// ещё не прочитанная часть строки
+    iter: CharsIter<'a>,
+    /// сегмент, отдаваемый с начала строки
+    front: Vec<Codepoint>,
+    /// позиция следующего отдаваемого кодпоинта в сегменте с начала строки
+    front_position: usize,
+    /// сегмент, отдаваемый с конца строки
+    back: Vec<Codepoint>,
+    /// позиция первого не отданного кодпоинта в сегменте с конца строки
+    back_position: usize,
+    /// прочитанный с начала строки символ, с которого начинается следующий сегмент
+    pending: Option<(u32, u32)>,
+}
+
+impl DecomposingNormalizer
+{
+    /// ленивая нормализация строки, результат - итератор символов
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    pub fn decompose_iter<'a>(&'a self, input: &'a str) -> DecomposeIter<'a>
+    {
+        DecomposeIter {
+            normalizer: self,
+            iter: CharsIter::new(input),
+            front: Vec::with_capacity(18),
+            front_position: 0,
+            back: Vec::new(),
+            back_position: 0,
+            pending: None,
+        }
+    }
+}
+
+impl<'a> DecomposeIter<'a>
+{
+    /// прочитать последний символ строки: данные о декомпозиции и код
+    #[inline(always)]
+    fn next_back_entry(&mut self) -> Option<(u32, u32)>
+    {
+        if self.iter.is_empty() {
+            return None;
+        }
+
+        let code = unsafe { self.iter.next_back_unchecked() };
+
+        match code < 0x80 {
+            true => Some((0, code)),
+            false => Some((self.normalizer.get_decomposition_value(code), code)),
+        }
+    }
+
+    /// заполнить сегмент, отдаваемый с начала строки
+    #[inline(never)]
+    fn fill_front(&mut self) -> bool
+    {
+        self.front_position = 0;
+
+        let normalizer = self.normalizer;
+        let iter = &mut self.iter;
+
+        fill_segment(normalizer, &mut self.front, &mut self.pending, || {
+            next_entry(normalizer, iter)
+        })
+    }
+
+    /// заполнить сегмент, отдаваемый с конца строки
+    #[inline(never)]
+    fn fill_back(&mut self) -> bool
+    {
+        // символы сегмента в обратном порядке
+        let mut entries: Vec<(u32, u32)> = Vec::new();
+
+        loop {
+            match self.next_back_entry() {
+                Some(entry) => {
+                    entries.push(entry);
+
+                    if self.normalizer.decomposition_starts_with_starter(entry.0) {
+                        break;
+                    }
+                }
+                None => {
+                    // строка прочитана до символа, уже прочитанного с начала - он и
+                    // является началом сегмента
+                    if let Some(entry) = self.pending.take() {
+                        entries.push(entry);
+                    }
+
+                    break;
+                }
+            }
+        }
+
+        if entries.is_empty() {
+            return false;
+        }
+
+        self.back.clear();
+        self.back_position = 0;
+
+        for &(value, code) in entries.iter().rev() {
+            self.normalizer
+                .decompose_to_buffer(value, code, &mut self.back);
+        }
+
+        sort_nonstarters(&mut self.back);
+        true
+    }
+}
+
+impl<'a> Iterator for DecomposeIter<'a>
+{
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char>
+    {
+        loop {
+            if self.front_position < self.front.len() {
+                let codepoint = self.front[self.front_position];
+                self.front_position += 1;
+
+                return Some(char::from(codepoint));
+            }
+
+            if !self.fill_front() {
+                break;
+            }
+        }
+
+        // строка прочитана полностью - остаток сегмента, отдаваемого с конца строки
+        match self.back_position < self.back.len() {
+            true => {
+                let codepoint = self.back[self.back_position];
+                self.back_position += 1;
+
+                Some(char::from(codepoint))
+            }
+            false => None,
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for DecomposeIter<'a>
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<char>
+    {
+        loop {
+            if self.back_position < self.back.len() {
+                return self.back.pop().map(char::from);
+            }
+
+            if !self.fill_back() {
+                break;
+            }
+        }
+
+        // строка прочитана полностью - остаток сегмента, отдаваемого с начала строки
+        match self.front_position < self.front.len() {
+            true => self.front.pop().map(char::from),
+            false => None,
+        }
+    }
+}
+
+/// NF(K)D нормализация произвольного источника символов
+pub struct DecomposeChars<'a, I>
+{
+    /// нормализатор
+    normalizer: &'a DecomposingNormalizer,
+    /// источник символов
+    source: I,
+    /// текущий сегмент
+    segment: Vec<Codepoint>,
+    /// позиция следующего отдаваемого кодпоинта в сегменте
+    position: usize,
+    /// прочитанный символ, с которого начинается следующий сегмент
+    pending: Option<(u32, u32)>,
+}
+
+/// NF(K)D нормализация итератора символов
+pub trait DecomposeCharsExt: Iterator<Item = char> + Sized
+{
+    /// NFD-нормализация
+    fn nfd(self) -> DecomposeChars<'static, Self>;
+
+    /// NFKD-нормализация
+    fn nfkd(self) -> DecomposeChars<'static, Self>;
+
+    /// нормализация с использованием данного нормализатора
+    fn decompose_with(self, normalizer: &DecomposingNormalizer) -> DecomposeChars<'_, Self>;
+}
+
+impl<I: Iterator<Item = char>> DecomposeCharsExt for I
+{
+    fn nfd(self) -> DecomposeChars<'static, Self>
+    {
+        self.decompose_with(DecomposingNormalizer::nfd())
+    }
+
+    fn nfkd(self) -> DecomposeChars<'static, Self>
+    {
+        self.decompose_with(DecomposingNormalizer::nfkd())
+    }
+
+    fn decompose_with(self, normalizer: &DecomposingNormalizer) -> DecomposeChars<'_, Self>
+    {
+        DecomposeChars {
+            normalizer,
+            source: self,
+            segment: Vec::with_capacity(18),
+            position: 0,
+            pending: None,
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = char>> Iterator for DecomposeChars<'a, I>
+{
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char>
+    {
+        loop {
+            if self.position < self.segment.len() {
+                let codepoint = self.segment[self.position];
+                self.position += 1;
+
+                return Some(char::from(codepoint));
+            }
+
+            self.position = 0;
+
+            let normalizer = self.normalizer;
+            let source = &mut self.source;
+
+            let filled = fill_segment(normalizer, &mut self.segment, &mut self.pending, || {
+                source.next().map(|c| {
+                    let code = c as u32;
+
+                    match code < 0x80 {
+                        true => (0, code),
+                        false => (normalizer.get_decomposition_value(code), code),
+                    }
+                })
+            });
+
+            if !filled {
+                return None;
+            }
+        }
+    }
+}
+
+/// прочитать следующий символ строки: данные о декомпозиции и код
+#[inline(always)]
+pub(crate) fn next_entry(
+    normalizer: &DecomposingNormalizer,
+    iter: &mut CharsIter,
+) -> Option<(u32, u32)>
+{
+    if iter.is_empty() {
+        return None;
+    }
+
+    let first = unsafe { iter.next_unchecked() };
+
+    if first < 0xC2 {
+        return Some((0, first as u32));
+    }
+
+    let code = unsafe { iter.next_nonascii_bytes_unchecked(first) };
+
+    Some((normalizer.get_decomposition_value(code), code))
+}
+
+/// заполнить сегмент: символ, прочитанный ранее (или первый прочитанный), и следующие за ним
+/// символы, декомпозиция которых начинается с нестартера; символ, с декомпозиции которого
+/// начинается следующий сегмент, сохраняется
+#[inline(always)]
+fn fill_segment(
+    normalizer: &DecomposingNormalizer,
+    segment: &mut Vec<Codepoint>,
+    pending: &mut Option<(u32, u32)>,
+    mut next_entry: impl FnMut() -> Option<(u32, u32)>,
+) -> bool
+{
+    segment.clear();
+
+    let (value, code) = match pending.take().or_else(&mut next_entry) {
+        Some(entry) => entry,
+        None => return false,
+    };
+
+    normalizer.decompose_to_buffer(value, code, segment);
+
+    while let Some((value, code)) = next_entry() {
+        if normalizer.decomposition_starts_with_starter(value) {
+            *pending = Some((value, code));
+            break;
+        }
+
+        normalizer.decompose_to_buffer(value, code, segment);
+    }
+
+    sort_nonstarters(segment);
+    true
+}
@@ -0,0 +1,129 @@
+use core::fmt;
+use core::ops::Deref;
+
+use crate::codepoint::{Codepoint, CodepointBuffer};
+use crate::{sort_nonstarters, DecomposingNormalizer};
+
+/// максимальная длина полной декомпозиции одного символа (U+FDFA в NFKD); более длинные
+/// декомпозиции не проходят DecompositionData::validate
+pub(crate) const CAPACITY: usize = 18;
+
+/// полная NF(K)D декомпозиция одного символа, не требующая выделения памяти
+#[derive(Clone, Copy)]
+pub struct Decomposed
+{
+    /// символы декомпозиции
+    chars: [char; CAPACITY],
+    /// количество символов
+    len: u8,
+}
+
+impl DecomposingNormalizer
+{
+    /// полная декомпозиция символа, упорядоченная по CCC
+    #[inline]
+    pub fn decompose_char(&self, c: char) -> Decomposed
+    {
+        let code = c as u32;
+
+        let mut buffer = DecomposedBuffer::new();
+
+        match code < 0x80 {
+            true => buffer.push(Codepoint::from_code(code)),
+            false => {
+                let value = self.get_decomposition_value(code);
+
+                self.decompose_to_buffer(value, code, &mut buffer);
+                sort_nonstarters(&mut buffer.codepoints[.. buffer.len]);
+            }
+        }
+
+        let mut chars = ['\0'; CAPACITY];
+
+        for (c, &codepoint) in chars.iter_mut().zip(buffer.codepoints()) {
+            *c = char::from(codepoint);
+        }
+
+        Decomposed {
+            chars,
+            len: buffer.len as u8,
+        }
+    }
+}
+
+impl Deref for Decomposed
+{
+    type Target = [char];
+
+    #[inline(always)]
+    fn deref(&self) -> &[char]
+    {
+        &self.chars[.. self.len as usize]
+    }
+}
+
+impl<'a> IntoIterator for &'a Decomposed
+{
+    type Item = &'a char;
+    type IntoIter = core::slice::Iter<'a, char>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter
+    {
+        self.iter()
+    }
+}
+
+impl PartialEq for Decomposed
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        **self == **other
+    }
+}
+
+impl Eq for Decomposed {}
+
+impl fmt::Debug for Decomposed
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// буфер кодпоинтов декомпозиции на стеке
+pub(crate) struct DecomposedBuffer
+{
+    pub codepoints: [Codepoint; CAPACITY],
+    pub len: usize,
+}
+
+impl DecomposedBuffer
+{
+    #[inline(always)]
+    pub fn new() -> Self
+    {
+        Self {
+            codepoints: [Codepoint::from_code(0); CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// записанные кодпоинты
+    #[inline(always)]
+    pub fn codepoints(&self) -> &[Codepoint]
+    {
+        &self.codepoints[.. self.len]
+    }
+}
+
+impl CodepointBuffer for DecomposedBuffer
+{
+    #[inline(always)]
+    fn push(&mut self, codepoint: Codepoint)
+    {
+        self.codepoints[self.len] = codepoint;
+        self.len += 1;
+    }
+}
@@ -0,0 +1,49 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::codepoint::Codepoint;
+use crate::sink::FmtSink;
+use crate::DecomposingNormalizer;
+
+/// строка, нормализуемая при форматировании - без промежуточной строки, если не заданы
+/// ширина или точность
+#[derive(Clone, Copy)]
+pub struct NormalizedDisplay<'a>
+{
+    normalizer: &'a DecomposingNormalizer,
+    input: &'a str,
+}
+
+impl DecomposingNormalizer
+{
+    /// нормализация строки при её форматировании: `write!(f, "{}", nfd.display(s))`
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline]
+    pub fn display<'a>(&'a self, input: &'a str) -> NormalizedDisplay<'a>
+    {
+        NormalizedDisplay {
+            normalizer: self,
+            input,
+        }
+    }
+}
+
+impl<'a> fmt::Display for NormalizedDisplay<'a>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        // для выравнивания и обрезки нужна длина результата - нормализуем строку целиком
+
+        if f.width().is_some() || f.precision().is_some() {
+            return f.pad(&self.normalizer.normalize(self.input));
+        }
+
+        let mut sink = FmtSink::new(f);
+        let mut buffer: Vec<Codepoint> = Vec::new();
+
+        self.normalizer
+            .normalize_inline(self.input, &mut sink, &mut buffer);
+
+        sink.result()
+    }
+}
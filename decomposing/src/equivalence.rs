@@ -0,0 +1,169 @@
+use crate::codepoint::{Codepoint, InlineBuffer, NonstarterBuffer};
+use crate::decompose_iter::next_entry;
+use crate::slice::iter::CharsIter;
+use crate::{sort_nonstarters, DecomposingNormalizer};
+
+/// сегмент декомпозиции не помещается в буфер на стеке
+struct SegmentOverflow;
+
+/// NF(K)D-декомпозиция строки посегментно в буфер на стеке
+struct InlineSegments<'a>
+{
+    /// нормализатор
+    normalizer: &'a DecomposingNormalizer,
+    /// ещё не прочитанная часть строки
+    iter: CharsIter<'a>,
+    /// текущий сегмент
+    segment: InlineBuffer,
+    /// позиция следующего отдаваемого кодпоинта в сегменте
+    position: usize,
+    /// прочитанный символ, с которого начинается следующий сегмент
+    pending: Option<(u32, u32)>,
+}
+
+impl DecomposingNormalizer
+{
+    /// строки канонически эквивалентны - их NFD-формы совпадают?
+    /// исходные строки должны являться well-formed UTF-8 строками
+    #[inline(never)]
+    pub fn canonically_equal(a: &str, b: &str) -> bool
+    {
+        Self::nfd().normalized_eq(a, b)
+    }
+
+    /// строки эквивалентны по совместимости - их NFKD-формы совпадают?
+    /// исходные строки должны являться well-formed UTF-8 строками
+    #[inline(never)]
+    pub fn compatibly_equal(a: &str, b: &str) -> bool
+    {
+        Self::nfkd().normalized_eq(a, b)
+    }
+
+    /// нормализованные формы строк совпадают? строки нормализуются одновременно, сравнение
+    /// заканчивается на первом различии
+    /// исходные строки должны являться well-formed UTF-8 строками
+    #[inline(never)]
+    pub fn normalized_eq(&self, a: &str, b: &str) -> bool
+    {
+        if a == b {
+            return true;
+        }
+
+        // общее начало из ASCII не меняется при нормализации, а т.к. ASCII - стартеры,
+        // нестартеры после него не могут быть переставлены с символами до него
+
+        let prefix = a
+            .bytes()
+            .zip(b.bytes())
+            .take_while(|&(x, y)| x == y && x < 0x80)
+            .count();
+
+        let (a, b) = (&a[prefix ..], &b[prefix ..]);
+
+        // декомпозиция любого символа не пуста, декомпозиция ASCII - сам символ
+
+        match (a.as_bytes().first(), b.as_bytes().first()) {
+            (None, _) | (_, None) => return false,
+            (Some(&x), Some(&y)) if x < 0x80 && y < 0x80 => return false,
+            _ => (),
+        }
+
+        // сегменты сравниваются в буферах на стеке; сегмент, не помещающийся в буфер (более
+        // 30 нестартеров подряд - строка не в Stream-Safe Text Format), сравнивается заново
+        // итераторами, выделяющими память
+
+        match segments_eq(self, a, b) {
+            Ok(equal) => equal,
+            Err(SegmentOverflow) => self.decompose_iter(a).eq(self.decompose_iter(b)),
+        }
+    }
+}
+
+/// сравнить декомпозиции строк посегментно
+#[inline(always)]
+fn segments_eq(
+    normalizer: &DecomposingNormalizer,
+    a: &str,
+    b: &str,
+) -> Result<bool, SegmentOverflow>
+{
+    let mut a = InlineSegments::new(normalizer, a);
+    let mut b = InlineSegments::new(normalizer, b);
+
+    loop {
+        let (x, y) = (a.next()?, b.next()?);
+
+        if x.map(|c| c.code()) != y.map(|c| c.code()) {
+            return Ok(false);
+        }
+
+        if x.is_none() {
+            return Ok(true);
+        }
+    }
+}
+
+impl<'a> InlineSegments<'a>
+{
+    #[inline(always)]
+    fn new(normalizer: &'a DecomposingNormalizer, input: &'a str) -> Self
+    {
+        Self {
+            normalizer,
+            iter: CharsIter::new(input),
+            segment: InlineBuffer::new(),
+            position: 0,
+            pending: None,
+        }
+    }
+
+    /// следующий кодпоинт декомпозиции
+    #[inline(always)]
+    fn next(&mut self) -> Result<Option<Codepoint>, SegmentOverflow>
+    {
+        if self.position == self.segment.len && !self.fill()? {
+            return Ok(None);
+        }
+
+        self.position += 1;
+
+        Ok(Some(self.segment.codepoints[self.position - 1]))
+    }
+
+    /// заполнить сегмент, как fill_segment; false - строка прочитана полностью
+    #[inline(never)]
+    fn fill(&mut self) -> Result<bool, SegmentOverflow>
+    {
+        let normalizer = self.normalizer;
+
+        self.segment.clear();
+        self.position = 0;
+
+        let (value, code) = match self
+            .pending
+            .take()
+            .or_else(|| next_entry(normalizer, &mut self.iter))
+        {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+
+        normalizer.decompose_to_buffer(value, code, &mut self.segment);
+
+        while let Some((value, code)) = next_entry(normalizer, &mut self.iter) {
+            if normalizer.decomposition_starts_with_starter(value) {
+                self.pending = Some((value, code));
+                break;
+            }
+
+            if self.segment.is_full() {
+                return Err(SegmentOverflow);
+            }
+
+            normalizer.decompose_to_buffer(value, code, &mut self.segment);
+        }
+
+        sort_nonstarters(self.segment.codepoints());
+        Ok(true)
+    }
+}
@@ -1,7 +1,22 @@
 use core::str::from_utf8_unchecked;
 
+use crate::codepoint::{Codepoint, CodepointBuffer};
+use crate::sink::DecompositionSink;
+
 /// начало блока слогов хангыль
 const HANGUL_S_BASE: u32 = 0xAC00;
+/// начальная согласная (L) чамо
+const HANGUL_L_BASE: u32 = 0x1100;
+/// гласная (V) чамо
+const HANGUL_V_BASE: u32 = 0x1161;
+/// кодпоинт, предшествующий завершающим согласным (T) чамо
+const HANGUL_T_BASE: u32 = 0x11A7;
+/// количество начальных согласных
+const HANGUL_L_COUNT: u32 = 19;
+/// количество гласных
+const HANGUL_V_COUNT: u32 = 21;
+/// количество слогов хангыль
+const HANGUL_S_COUNT: u32 = 11172;
 /// количество гласных * количество завершающих согласных
 const HANGUL_N_COUNT: u32 = 588;
 /// количество завершающих согласных
@@ -11,7 +26,7 @@ const HANGUL_T_BLOCK_SIZE: u32 = HANGUL_T_COUNT + 1;
 
 /// декомпозиция слога хангыль
 #[inline(never)]
-pub fn decompose_hangul_syllable(result: &mut String, code: u32)
+pub fn decompose_hangul_syllable(result: &mut impl DecompositionSink, code: u32)
 {
     let lvt = code.wrapping_sub(HANGUL_S_BASE);
 
@@ -23,14 +38,60 @@ pub fn decompose_hangul_syllable(result: &mut String, code: u32)
     let c1 = 0xA1 + v;
 
     match t == 0 {
-        true => result.push_str(unsafe { from_utf8_unchecked(&[0xE1, 0x84, c0, 0xE1, 0x85, c1]) }),
+        true => result.write_str(unsafe { from_utf8_unchecked(&[0xE1, 0x84, c0, 0xE1, 0x85, c1]) }),
         false => {
             let c2 = 0x86 | ((0x07 + t) >> 5);
             let c3 = 0x80 | ((0xA7 + t) & 0x3F);
 
-            result.push_str(unsafe {
+            result.write_str(unsafe {
                 from_utf8_unchecked(&[0xE1, 0x84, c0, 0xE1, 0x85, c1, 0xE1, c2, c3])
             });
         }
     };
 }
+
+/// декомпозиция слога хангыль в буфер кодпоинтов
+#[inline(never)]
+pub fn decompose_hangul_syllable_to_buffer(buffer: &mut impl CodepointBuffer, code: u32)
+{
+    let lvt = code.wrapping_sub(HANGUL_S_BASE);
+
+    let l = lvt / HANGUL_N_COUNT;
+    let v = (lvt % HANGUL_N_COUNT) / HANGUL_T_BLOCK_SIZE;
+    let t = lvt % HANGUL_T_BLOCK_SIZE;
+
+    buffer.push(Codepoint::from_code(HANGUL_L_BASE + l));
+    buffer.push(Codepoint::from_code(HANGUL_V_BASE + v));
+
+    if t != 0 {
+        buffer.push(Codepoint::from_code(HANGUL_T_BASE + t));
+    }
+}
+
+/// композиция чамо хангыль: L + V -> LV, LV + T -> LVT
+#[inline(always)]
+pub fn compose_hangul(first: u32, second: u32) -> Option<u32>
+{
+    let l = first.wrapping_sub(HANGUL_L_BASE);
+
+    if l < HANGUL_L_COUNT {
+        let v = second.wrapping_sub(HANGUL_V_BASE);
+
+        return match v < HANGUL_V_COUNT {
+            true => Some(HANGUL_S_BASE + l * HANGUL_N_COUNT + v * HANGUL_T_BLOCK_SIZE),
+            false => None,
+        };
+    }
+
+    let lv = first.wrapping_sub(HANGUL_S_BASE);
+
+    if lv < HANGUL_S_COUNT && lv.is_multiple_of(HANGUL_T_BLOCK_SIZE) {
+        let t = second.wrapping_sub(HANGUL_T_BASE);
+
+        if t.wrapping_sub(1) < HANGUL_T_COUNT {
+            return Some(first + t);
+        }
+    }
+
+    None
+}
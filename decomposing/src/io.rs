@@ -0,0 +1,252 @@
+use std::io::{self, BufRead, Read, Write};
+
+use crate::bytes::Utf8NormalizeError;
+use crate::{DecomposingNormalizer, StreamingDecomposer};
+
+/// NF(K)D нормализация потока чтения: байты UTF-8 читаются из внутреннего буфера источника
+/// и отдаются нормализованными. некорректный UTF-8 - ошибка io::ErrorKind::InvalidData,
+/// содержащая Utf8NormalizeError со смещением от начала потока
+///
+/// источник должен реализовывать BufRead, источник Read оборачивается в BufReader:
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::{BufReader, Read};
+///
+/// use unicode_decomposing::NormalizingReader;
+///
+/// let file = File::open("text.txt")?;
+/// let mut normalized = String::new();
+///
+/// NormalizingReader::nfd(BufReader::new(file)).read_to_string(&mut normalized)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct NormalizingReader<'a, R>
+{
+    /// источник
+    inner: R,
+    /// потоковый нормализатор
+    stream: StreamingDecomposer<'a>,
+    /// нормализованная часть потока
+    output: String,
+    /// позиция первого не отданного байта нормализованной части
+    position: usize,
+    /// источник прочитан полностью
+    finished: bool,
+}
+
+impl<R: BufRead> NormalizingReader<'static, R>
+{
+    /// NFD-нормализация потока
+    pub fn nfd(inner: R) -> Self
+    {
+        Self::new(DecomposingNormalizer::nfd(), inner)
+    }
+
+    /// NFKD-нормализация потока
+    pub fn nfkd(inner: R) -> Self
+    {
+        Self::new(DecomposingNormalizer::nfkd(), inner)
+    }
+}
+
+impl<'a, R: BufRead> NormalizingReader<'a, R>
+{
+    /// нормализация потока с использованием данного нормализатора
+    pub fn new(normalizer: &'a DecomposingNormalizer, inner: R) -> Self
+    {
+        Self {
+            inner,
+            stream: StreamingDecomposer::new(normalizer),
+            output: String::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// источник
+    pub fn into_inner(self) -> R
+    {
+        self.inner
+    }
+}
+
+impl<'a, R: BufRead> BufRead for NormalizingReader<'a, R>
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]>
+    {
+        while self.position == self.output.len() && !self.finished {
+            self.output.clear();
+            self.position = 0;
+
+            let chunk = self.inner.fill_buf()?;
+            let length = chunk.len();
+
+            let result = match length {
+                0 => {
+                    self.finished = true;
+                    self.stream.finish(&mut self.output)
+                }
+                _ => self.stream.push_bytes(chunk, &mut self.output),
+            };
+
+            // часть фрагмента, нормализованная до ошибки, не отдаётся
+            if let Err(error) = result {
+                self.output.clear();
+                return Err(invalid_data(error));
+            }
+
+            self.inner.consume(length);
+        }
+
+        Ok(&self.output.as_bytes()[self.position ..])
+    }
+
+    fn consume(&mut self, amount: usize)
+    {
+        self.position = (self.position + amount).min(self.output.len());
+    }
+}
+
+impl<'a, R: BufRead> Read for NormalizingReader<'a, R>
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+    {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+
+        buf[.. length].copy_from_slice(&available[.. length]);
+        self.consume(length);
+
+        Ok(length)
+    }
+}
+
+/// NF(K)D нормализация потока записи: записанные байты UTF-8 нормализуются и передаются
+/// приёмнику. нестартеры в конце записанных данных задерживаются до следующей записи,
+/// поэтому поток следует завершать вызовом finish. при удалении без finish задержанные
+/// нестартеры записываются, ошибки при этом игнорируются (как в BufWriter)
+///
+/// нормализованные данные, которые приёмник не принял из-за ошибки, сохраняются и передаются
+/// ему при следующей записи, flush или finish - ошибка возвращается одним из этих вызовов,
+/// а исходные данные не нормализуются повторно, поэтому запись можно повторить
+pub struct NormalizingWriter<'a, W: Write>
+{
+    /// приёмник; забирается только into_inner
+    inner: Option<W>,
+    /// потоковый нормализатор
+    stream: StreamingDecomposer<'a>,
+    /// нормализованная часть потока, ещё не переданная приёмнику
+    output: String,
+    /// количество байт output, уже переданных приёмнику
+    written: usize,
+}
+
+impl<W: Write> NormalizingWriter<'static, W>
+{
+    /// NFD-нормализация потока
+    pub fn nfd(inner: W) -> Self
+    {
+        Self::new(DecomposingNormalizer::nfd(), inner)
+    }
+
+    /// NFKD-нормализация потока
+    pub fn nfkd(inner: W) -> Self
+    {
+        Self::new(DecomposingNormalizer::nfkd(), inner)
+    }
+}
+
+impl<'a, W: Write> NormalizingWriter<'a, W>
+{
+    /// нормализация потока с использованием данного нормализатора
+    pub fn new(normalizer: &'a DecomposingNormalizer, inner: W) -> Self
+    {
+        Self {
+            inner: Some(inner),
+            stream: StreamingDecomposer::new(normalizer),
+            output: String::new(),
+            written: 0,
+        }
+    }
+
+    /// завершить поток: записать оставшиеся нестартеры и сбросить приёмник. при ошибке
+    /// приёмника вызов можно повторить; после завершения в поток можно продолжить запись
+    pub fn finish(&mut self) -> io::Result<()>
+    {
+        self.write_pending()?;
+        self.stream.finish(&mut self.output).map_err(invalid_data)?;
+        self.write_pending()?;
+
+        self.inner.as_mut().unwrap().flush()
+    }
+
+    /// забрать приёмник; данные, ещё не переданные ему, отбрасываются - поэтому сначала
+    /// следует завершить поток вызовом finish
+    pub fn into_inner(mut self) -> W
+    {
+        self.inner.take().unwrap()
+    }
+
+    /// передать приёмнику сохранённые нормализованные данные
+    fn write_pending(&mut self) -> io::Result<()>
+    {
+        let inner = self.inner.as_mut().unwrap();
+
+        while self.written < self.output.len() {
+            match inner.write(&self.output.as_bytes()[self.written ..]) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(length) => self.written += length,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => (),
+                Err(error) => return Err(error),
+            }
+        }
+
+        self.output.clear();
+        self.written = 0;
+
+        Ok(())
+    }
+}
+
+impl<'a, W: Write> Write for NormalizingWriter<'a, W>
+{
+    /// данные считаются записанными, как только они нормализованы: ошибка приёмника при их
+    /// передаче возвращается следующим вызовом
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+    {
+        self.write_pending()?;
+        self.stream
+            .push_bytes(buf, &mut self.output)
+            .map_err(invalid_data)?;
+
+        let _ = self.write_pending();
+
+        Ok(buf.len())
+    }
+
+    /// сбрасывается приёмник: задержанные нестартеры записываются при завершении потока
+    fn flush(&mut self) -> io::Result<()>
+    {
+        self.write_pending()?;
+        self.inner.as_mut().unwrap().flush()
+    }
+}
+
+impl<'a, W: Write> Drop for NormalizingWriter<'a, W>
+{
+    /// записать задержанные нестартеры, если приёмник не был забран
+    fn drop(&mut self)
+    {
+        if self.inner.is_some() {
+            let _ = self.finish();
+        }
+    }
+}
+
+/// ошибка ввода-вывода для некорректного UTF-8
+#[inline(never)]
+fn invalid_data(error: Utf8NormalizeError) -> io::Error
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
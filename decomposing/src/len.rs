@@ -0,0 +1,60 @@
+use alloc::vec::Vec;
+
+use crate::codepoint::Codepoint;
+use crate::sink::{CountingSink, DecompositionSink};
+use crate::DecomposingNormalizer;
+
+impl DecomposingNormalizer
+{
+    /// длина нормализованной строки в байтах, сама строка не записывается
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalized_len(&self, input: &str) -> usize
+    {
+        let mut counter = CountingSink::new();
+        let mut buffer: Vec<Codepoint> = Vec::new();
+
+        self.normalize_inline(input, &mut counter, &mut buffer);
+
+        counter.len
+    }
+
+    /// количество символов нормализованной строки, сама строка не записывается
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalized_char_count(&self, input: &str) -> usize
+    {
+        let mut counter = CharCountingSink { count: 0 };
+        let mut buffer: Vec<Codepoint> = Vec::new();
+
+        self.normalize_inline(input, &mut counter, &mut buffer);
+
+        counter.count
+    }
+}
+
+/// приёмник, подсчитывающий количество символов результата
+struct CharCountingSink
+{
+    count: usize,
+}
+
+impl DecompositionSink for CharCountingSink
+{
+    #[inline(always)]
+    fn write_str(&mut self, string: &str)
+    {
+        // байты продолжения последовательностей не начинают символ
+        self.count += string
+            .as_bytes()
+            .iter()
+            .filter(|&&byte| (byte as i8) >= -0x40)
+            .count();
+    }
+
+    #[inline(always)]
+    fn write_char(&mut self, _: char)
+    {
+        self.count += 1;
+    }
+}
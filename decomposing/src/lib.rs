@@ -1,15 +1,55 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
 use core::str::from_utf8_unchecked;
 
+pub use binary::{DataError, DataHeader, UnicodeVersion};
+pub use bytes::Utf8NormalizeError;
 pub use codepoint::Codepoint;
+use codepoint::{CodepointBuffer, NonstarterBuffer};
+pub use composing::ComposingNormalizer;
 pub use data::DecompositionData;
-use hangul::decompose_hangul_syllable;
+pub use decompose_iter::{DecomposeChars, DecomposeCharsExt, DecomposeIter};
+pub use decomposed::Decomposed;
+pub use display::NormalizedDisplay;
+use hangul::{decompose_hangul_syllable, decompose_hangul_syllable_to_buffer};
+#[cfg(feature = "std")]
+pub use io::{NormalizingReader, NormalizingWriter};
+pub use offsets::{OffsetMap, OffsetSegment};
+pub use properties::DecompositionKind;
+pub use sink::{CountingSink, DecompositionSink, FmtSink};
 use slice::aligned::Aligned;
 pub use slice::iter::CharsIter;
+pub use slice::utf16::Utf16Iter;
+pub use stream::StreamingDecomposer;
+pub use utf16::LoneSurrogates;
 
+mod binary;
+mod bytes;
 mod codepoint;
+mod codepoints;
+mod composing;
 mod data;
+mod decompose_iter;
+mod decomposed;
+mod display;
+mod equivalence;
 mod hangul;
+#[cfg(feature = "std")]
+mod io;
+mod len;
+mod offsets;
+mod properties;
+mod sink;
 mod slice;
+mod stream;
+mod stream_safe;
+mod utf16;
 
 /// последний кодпоинт с декомпозицией (U+2FA1D), его блок - 0x5F4
 pub const LAST_DECOMPOSING_CODEPOINT_BLOCK: u16 = (0x2FA1D >> (18 - 11)) as u16;
@@ -34,7 +74,56 @@ pub const MARKER_EXPANSION_COMBINED_EMPTY: u8 = 0b_110;
 /// слог хангыль
 pub const MARKER_HANGUL: u8 = 0b_111;
 
+/// результат быстрой проверки, находится-ли строка в нормализованной форме
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsNormalized
+{
+    /// строка нормализована
+    Yes,
+    /// строка не нормализована
+    No,
+    /// строка может быть нормализована, для ответа требуется полная проверка;
+    /// для NF(K)D такой результат невозможен
+    Maybe,
+}
+
+/// результат нормализации не помещается в предоставленный срез
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NeedMore
+{
+    /// необходимая длина среза
+    pub required: usize,
+}
+
+impl fmt::Display for NeedMore
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "output buffer too small: {} required", self.required)
+    }
+}
+
+impl core::error::Error for NeedMore {}
+
+/// переиспользуемый между вызовами нормализации буфер нестартеров
+#[derive(Debug, Default)]
+pub struct NormalizerScratch
+{
+    buffer: Vec<Codepoint>,
+}
+
+impl NormalizerScratch
+{
+    pub fn new() -> Self
+    {
+        Self {
+            buffer: Vec::with_capacity(18),
+        }
+    }
+}
+
 // нормализатор NF(K)D
+#[derive(Clone)]
 #[repr(C, align(16))]
 pub struct DecomposingNormalizer
 {
@@ -46,8 +135,16 @@ pub struct DecomposingNormalizer
     expansions: Aligned<'static, u32>,
     /// с U+0000 и до этого кодпоинта включительно блоки в data идут последовательно
     continuous_block_end: u32,
+    /// версия Unicode данных, если она известна
+    unicode_version: Option<UnicodeVersion>,
 }
 
+// нормализатор только читает свои таблицы, поэтому может использоваться из нескольких потоков
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<DecomposingNormalizer>();
+};
+
 impl DecomposingNormalizer
 {
     /// нормализация строки
@@ -57,25 +154,191 @@ impl DecomposingNormalizer
     {
         let mut result = String::with_capacity(input.len());
         let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+
+        self.normalize_inline(input, &mut result, &mut buffer);
+
+        result
+    }
+
+    /// нормализация строки с дописыванием результата в существующую строку
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize_to(&self, input: &str, out: &mut String)
+    {
+        // буфер аллоцируется только при появлении нестартеров
+        let mut buffer: Vec<Codepoint> = Vec::new();
+
+        out.reserve(input.len());
+        self.normalize_inline(input, out, &mut buffer);
+    }
+
+    /// нормализация строки с дописыванием результата в существующую строку,
+    /// буфер нестартеров берётся из переиспользуемого scratch
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize_to_with_scratch(
+        &self,
+        input: &str,
+        out: &mut String,
+        scratch: &mut NormalizerScratch,
+    )
+    {
+        out.reserve(input.len());
+        self.normalize_inline(input, out, &mut scratch.buffer);
+    }
+
+    /// нормализация строки с записью результата в приёмник
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize_with_sink(&self, input: &str, sink: &mut impl DecompositionSink)
+    {
+        // буфер аллоцируется только при появлении нестартеров
+        let mut buffer: Vec<Codepoint> = Vec::new();
+
+        self.normalize_inline(input, sink, &mut buffer);
+    }
+
+    /// нормализация строки; если строка уже нормализована - возвращаем её без копирования,
+    /// иначе результат аллоцируется, начиная с первого изменяемого участка
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize_cow<'a>(&self, input: &'a str) -> Cow<'a, str>
+    {
+        let prefix = self.normalized_prefix_len(input);
+
+        if prefix == input.len() {
+            return Cow::Borrowed(input);
+        }
+
+        let mut result = String::with_capacity(input.len());
+        let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+
+        result.push_str(&input[.. prefix]);
+        self.normalize_inline(&input[prefix ..], &mut result, &mut buffer);
+
+        Cow::Owned(result)
+    }
+
+    /// нормализация строки; если строка уже нормализована - возвращаем её же
+    #[inline(never)]
+    pub fn normalize_owned(&self, input: String) -> String
+    {
+        let prefix = self.normalized_prefix_len(&input);
+
+        if prefix == input.len() {
+            return input;
+        }
+
+        let mut result = String::with_capacity(input.len());
+        let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+
+        result.push_str(&input[.. prefix]);
+        self.normalize_inline(&input[prefix ..], &mut result, &mut buffer);
+
+        result
+    }
+
+    /// основной цикл нормализации, результат дописывается в строку;
+    /// буфер нестартеров должен быть пуст, по окончании он также остаётся пустым
+    #[inline(always)]
+    fn normalize_inline(
+        &self,
+        input: &str,
+        result: &mut impl DecompositionSink,
+        buffer: &mut Vec<Codepoint>,
+    )
+    {
         let iter = &mut CharsIter::new(input);
 
         loop {
             let entry = match !buffer.is_empty() {
-                true => match self.forward(iter, &mut result, &mut buffer) {
+                true => match self.forward(iter, result, buffer) {
                     Some(entry) => Some(entry),
                     None => continue,
                 },
-                false => self.fast_forward(iter, &mut result),
+                false => self.fast_forward(iter, result),
             };
 
             match entry {
                 Some((dec_value, code)) => {
-                    self.handle_decomposition_value(dec_value, code, &mut result, &mut buffer);
+                    self.handle_decomposition_value(dec_value, code, result, buffer);
                     iter.set_breakpoint();
                 }
-                None => return result,
+                None => return,
+            }
+        }
+    }
+
+    /// строка находится в форме NF(K)D?
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn is_normalized(&self, input: &str) -> bool
+    {
+        self.quick_check(input) == IsNormalized::Yes
+    }
+
+    /// быстрая проверка NFD_QC / NFKD_QC с учётом порядка нестартеров по CCC, результат - Yes или No
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn quick_check(&self, input: &str) -> IsNormalized
+    {
+        match self.normalized_prefix_len(input) == input.len() {
+            true => IsNormalized::Yes,
+            false => IsNormalized::No,
+        }
+    }
+
+    /// длина начального участка строки, который не изменится при нормализации: участок
+    /// заканчивается перед последовательностью нестартеров, в которой встретился символ
+    /// с декомпозицией или нарушен порядок CCC
+    #[inline(always)]
+    fn normalized_prefix_len(&self, input: &str) -> usize
+    {
+        let iter = &mut CharsIter::new(input);
+        let mut last_ccc = 0;
+
+        loop {
+            if iter.is_empty() {
+                return input.len();
+            }
+
+            let first = unsafe { iter.next_unchecked() };
+
+            // как и в цикле быстрой проверки при нормализации, символы до U+00C0 (U+00A0)
+            // декомпозиции не имеют, но являются стартерами
+
+            if first < 0xC2 {
+                last_ccc = 0;
+                iter.set_breakpoint();
+                continue;
+            }
+
+            let code = unsafe { iter.next_nonascii_bytes_unchecked(first) };
+            let dec_value = self.get_decomposition_value(code);
+
+            if (dec_value as u8 >> 2) == 0 {
+                last_ccc = 0;
+                iter.set_breakpoint();
+                continue;
+            }
+
+            // нестартеры без декомпозиции должны следовать в порядке возрастания CCC,
+            // любой символ с декомпозицией означает, что строка не нормализована
+
+            if (dec_value as u8) >> 1 != MARKER_NONSTARTER {
+                break;
             }
+
+            let ccc = (dec_value >> 8) as u8;
+
+            if ccc < last_ccc {
+                break;
+            }
+
+            last_ccc = ccc;
         }
+
+        input.len() - iter.ending_slice().len()
     }
 
     /// если буфер не пуст, мы не можем перейти к быстрой проверке.
@@ -84,7 +347,7 @@ impl DecomposingNormalizer
     fn forward(
         &self,
         iter: &mut CharsIter,
-        result: &mut String,
+        result: &mut impl DecompositionSink,
         buffer: &mut Vec<Codepoint>,
     ) -> Option<(u32, u32)>
     {
@@ -109,7 +372,11 @@ impl DecomposingNormalizer
 
     /// цикл быстрой проверки, является-ли часть строки уже нормализованной
     #[inline(always)]
-    fn fast_forward(&self, iter: &mut CharsIter, result: &mut String) -> Option<(u32, u32)>
+    fn fast_forward(
+        &self,
+        iter: &mut CharsIter,
+        result: &mut impl DecompositionSink,
+    ) -> Option<(u32, u32)>
     {
         loop {
             if iter.is_empty() {
@@ -161,8 +428,8 @@ impl DecomposingNormalizer
         &self,
         value: u32,
         code: u32,
-        result: &mut String,
-        buffer: &mut Vec<Codepoint>,
+        result: &mut impl DecompositionSink,
+        buffer: &mut impl NonstarterBuffer,
     )
     {
         let marker = (value as u8) >> 1;
@@ -173,7 +440,7 @@ impl DecomposingNormalizer
             }
             MARKER_SINGLETON => {
                 flush(result, buffer);
-                write_char(result, (value >> 8) as u32);
+                write_char(result, value >> 8);
             }
             MARKER_EXPANSION => {
                 handle_expansion(value, result, buffer, &self.expansions, false);
@@ -194,12 +461,59 @@ impl DecomposingNormalizer
 
                 match ccc != 0 {
                     true => buffer.push(Codepoint::from_code_and_ccc(c2, ccc)),
-                    false => write_char(result, c2 as u32),
+                    false => write_char(result, c2),
                 }
             }
         }
     }
 
+    /// записать в буфер полную декомпозицию кодпоинта, не сортируя её и не записывая стартеры
+    /// в результат
+    #[inline(always)]
+    fn decompose_to_buffer(&self, value: u32, code: u32, buffer: &mut impl CodepointBuffer)
+    {
+        let marker = (value as u8) >> 1;
+
+        match marker {
+            MARKER_STARTER | MARKER_COMBINES_BACKWARDS => buffer.push(Codepoint::from_code(code)),
+            MARKER_NONSTARTER => {
+                buffer.push(Codepoint::from_code_and_ccc(code, (value >> 8) as u8))
+            }
+            MARKER_SINGLETON => buffer.push(Codepoint::from_code(value >> 8)),
+            MARKER_EXPANSION => decompose_expansion(value, buffer, &self.expansions, false),
+            MARKER_EXPANSION_COMBINED_PATCH | MARKER_EXPANSION_COMBINED_EMPTY => {
+                decompose_expansion(value, buffer, &self.expansions, true)
+            }
+            MARKER_HANGUL => decompose_hangul_syllable_to_buffer(buffer, code),
+            _ => {
+                let c2 = value >> 16;
+                let c2_value = self.get_decomposition_value(c2);
+
+                let ccc = match (c2_value as u8) >> 1 == MARKER_NONSTARTER {
+                    true => (c2_value >> 8) as u8,
+                    false => 0,
+                };
+
+                buffer.push(Codepoint::from_code((value as u16 >> 1) as u32));
+                buffer.push(Codepoint::from_code_and_ccc(c2, ccc));
+            }
+        }
+    }
+
+    /// декомпозиция символа начинается со стартера?
+    #[inline(always)]
+    fn decomposition_starts_with_starter(&self, value: u32) -> bool
+    {
+        match (value as u8) >> 1 {
+            MARKER_NONSTARTER => false,
+            MARKER_EXPANSION => self.expansions[(value >> 18) as usize] as u8 == 0,
+            MARKER_EXPANSION_COMBINED_PATCH | MARKER_EXPANSION_COMBINED_EMPTY => {
+                self.expansions[(value >> 18) as usize + 1] as u8 == 0
+            }
+            _ => true,
+        }
+    }
+
     /// данные о декомпозиции символа
     #[inline(always)]
     fn get_decomposition_value(&self, code: u32) -> u32
@@ -219,13 +533,21 @@ impl DecomposingNormalizer
         };
 
         let code_offsets = (code as u16) & 0x7F;
-        let data_block_index = data_block_base | (code_offsets >> 3) as u16;
+        let data_block_index = data_block_base | (code_offsets >> 3);
         let index = self.index[data_block_index as usize] | code_offsets & 0x7;
 
         self.data[index as usize]
     }
 
-    /// заранее подготовленные данные
+    /// заранее подготовленные данные, проверенные DecompositionData::validate
+    pub fn try_from_baked(source: DecompositionData) -> Result<Self, DataError>
+    {
+        source.validate()?;
+
+        Ok(Self::from_baked(source))
+    }
+
+    /// заранее подготовленные данные, копируются в выровненные блоки памяти
     pub fn from_baked(source: DecompositionData) -> Self
     {
         Self {
@@ -233,19 +555,68 @@ impl DecomposingNormalizer
             index: Aligned::from(source.index),
             expansions: Aligned::from(source.expansions),
             continuous_block_end: source.continuous_block_end,
+            unicode_version: None,
+        }
+    }
+
+    /// заранее подготовленные статические данные, используются без копирования;
+    /// для лучшей производительности таблицы должны быть выровнены по 16 байт
+    pub const fn from_static(source: DecompositionData<'static>) -> Self
+    {
+        Self {
+            data: Aligned::borrowed(source.data),
+            index: Aligned::borrowed(source.index),
+            expansions: Aligned::borrowed(source.expansions),
+            continuous_block_end: source.continuous_block_end,
+            unicode_version: None,
         }
     }
 
-    /// NFD-нормализатор
-    pub fn new_nfd() -> Self
+    /// указать версию Unicode данных нормализатора
+    pub const fn with_unicode_version(mut self, unicode_version: UnicodeVersion) -> Self
+    {
+        self.unicode_version = Some(unicode_version);
+        self
+    }
+
+    /// версия Unicode данных нормализатора: записана в бинарных данных, указана
+    /// with_unicode_version или задана для встроенных таблиц; для данных, переданных
+    /// в from_baked / from_static без указания версии, - неизвестна
+    pub fn unicode_version(&self) -> Option<UnicodeVersion>
+    {
+        self.unicode_version
+    }
+
+    /// NFD-нормализатор на статических данных, не выделяет память:
+    /// `static NFD: DecomposingNormalizer = DecomposingNormalizer::new_nfd();`
+    pub const fn new_nfd() -> Self
     {
-        Self::from_baked(data::nfd())
+        Self::from_static(data::nfd()).with_unicode_version(data::UNICODE_VERSION)
     }
 
-    /// NFKD-нормализатор
-    pub fn new_nfkd() -> Self
+    /// NFKD-нормализатор на статических данных, не выделяет память
+    pub const fn new_nfkd() -> Self
     {
-        Self::from_baked(data::nfkd())
+        Self::from_static(data::nfkd()).with_unicode_version(data::UNICODE_VERSION)
+    }
+
+    /// общий для всего процесса NFD-нормализатор: данные статические, поэтому при первом
+    /// обращении ничего не вычисляется и не копируется
+    #[inline(always)]
+    pub fn nfd() -> &'static Self
+    {
+        static NFD: DecomposingNormalizer = DecomposingNormalizer::new_nfd();
+
+        &NFD
+    }
+
+    /// общий для всего процесса NFKD-нормализатор
+    #[inline(always)]
+    pub fn nfkd() -> &'static Self
+    {
+        static NFKD: DecomposingNormalizer = DecomposingNormalizer::new_nfkd();
+
+        &NFKD
     }
 }
 
@@ -253,8 +624,8 @@ impl DecomposingNormalizer
 #[inline(always)]
 fn handle_expansion(
     value: u32,
-    result: &mut String,
-    buffer: &mut Vec<Codepoint>,
+    result: &mut impl DecompositionSink,
+    buffer: &mut impl NonstarterBuffer,
     expansions: &[u32],
     shift: bool,
 )
@@ -281,23 +652,46 @@ fn handle_expansion(
         .for_each(|&entry| buffer.push(Codepoint::from_baked(entry)));
 }
 
+/// данные записаны в дополнительном блоке, записываем их в буфер целиком
+#[inline(always)]
+fn decompose_expansion(
+    value: u32,
+    buffer: &mut impl CodepointBuffer,
+    expansions: &[u32],
+    shift: bool,
+)
+{
+    let count = (value >> 13) & 0x1F;
+    let mut index = value >> 18;
+
+    if shift {
+        index += 1;
+    }
+
+    expansions[index as usize .. (index + count) as usize]
+        .iter()
+        .for_each(|&entry| buffer.push(Codepoint::from_baked(entry)));
+}
+
 /// не-инлайн вариант функции
 #[inline(never)]
-fn flush(result: &mut String, buffer: &mut Vec<Codepoint>)
+fn flush(result: &mut impl DecompositionSink, buffer: &mut impl NonstarterBuffer)
 {
     flush_inline(result, buffer)
 }
 
 /// отсортировать кодпоинты буфера по CCC, записать в результат и освободить буфер
 #[inline(always)]
-fn flush_inline(result: &mut String, buffer: &mut Vec<Codepoint>)
+fn flush_inline(result: &mut impl DecompositionSink, buffer: &mut impl NonstarterBuffer)
 {
-    if !buffer.is_empty() {
-        if buffer.len() > 1 {
-            buffer.sort_by_key(|codepoint| codepoint.ccc());
+    let codepoints = buffer.codepoints();
+
+    if !codepoints.is_empty() {
+        if codepoints.len() > 1 {
+            codepoints.sort_by_key(|codepoint| codepoint.packed_ccc());
         }
 
-        for &codepoint in buffer.iter() {
+        for &codepoint in codepoints.iter() {
             write(result, codepoint);
         }
 
@@ -305,23 +699,40 @@ fn flush_inline(result: &mut String, buffer: &mut Vec<Codepoint>)
     };
 }
 
+/// отсортировать по CCC каждую последовательность нестартеров, не меняя положение стартеров
+#[inline(always)]
+fn sort_nonstarters(buffer: &mut [Codepoint])
+{
+    let mut start = 0;
+
+    for i in 0 ..= buffer.len() {
+        if i == buffer.len() || buffer[i].is_starter() {
+            if i - start > 1 {
+                buffer[start .. i].sort_by_key(|codepoint| codepoint.packed_ccc());
+            }
+
+            start = i + 1;
+        }
+    }
+}
+
 /// дописать символ(по коду) в результат
 #[inline(always)]
-fn write_char(result: &mut String, code: u32)
+fn write_char(result: &mut impl DecompositionSink, code: u32)
 {
-    result.push(unsafe { char::from_u32_unchecked(code) });
+    result.write_char(unsafe { char::from_u32_unchecked(code) });
 }
 
 /// дописать кодпоинт в UTF-8 результат
 #[inline(always)]
-fn write(result: &mut String, codepoint: Codepoint)
+fn write(result: &mut impl DecompositionSink, codepoint: Codepoint)
 {
-    result.push(char::from(codepoint));
+    result.write_char(char::from(codepoint));
 }
 
 /// дописать уже нормализованный кусок исходной строки в UTF-8 результат
 #[inline(always)]
-fn write_str(result: &mut String, string: &[u8])
+fn write_str(result: &mut impl DecompositionSink, string: &[u8])
 {
-    result.push_str(unsafe { from_utf8_unchecked(string) });
+    result.write_str(unsafe { from_utf8_unchecked(string) });
 }
@@ -0,0 +1,172 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::codepoint::Codepoint;
+use crate::{flush, write_char, DecomposingNormalizer};
+
+/// соответствие участка исходной строки участку результата нормализации
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OffsetSegment
+{
+    /// диапазон исходной строки, в байтах
+    pub source: Range<usize>,
+    /// диапазон результата, в байтах
+    pub output: Range<usize>,
+    /// участок изменён нормализацией; смещения внутри неизменённого участка соответствуют
+    /// друг другу, внутри изменённого - только его границы
+    pub changed: bool,
+}
+
+/// соответствие смещений исходной строки и результата нормализации. участки следуют подряд
+/// и покрывают обе строки целиком; изменённый участок - стартер вместе со следующими за ним
+/// нестартерами, т.к. каноническая сортировка не переносит нестартеры через стартеры
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OffsetMap
+{
+    segments: Vec<OffsetSegment>,
+}
+
+impl DecomposingNormalizer
+{
+    /// нормализация строки с соответствием смещений исходной строки и результата
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize_with_offsets(&self, input: &str) -> (String, OffsetMap)
+    {
+        let mut result = String::with_capacity(input.len());
+        let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+        let mut map = OffsetMap::default();
+
+        // начало текущего участка в исходной строке и в результате
+        let mut source_start = 0;
+        let mut output_start = 0;
+
+        for (offset, c) in input.char_indices() {
+            let code = c as u32;
+            let dec_value = self.get_decomposition_value(code);
+
+            // декомпозиция начинается со стартера - нестартеры буфера не будут переставлены
+            // с ней, участок завершён
+
+            if self.decomposition_starts_with_starter(dec_value) {
+                flush(&mut result, &mut buffer);
+                map.push(input, &result, source_start .. offset, output_start);
+
+                source_start = offset;
+                output_start = result.len();
+            }
+
+            match (dec_value as u8 >> 2) == 0 {
+                true => write_char(&mut result, code),
+                false => self.handle_decomposition_value(dec_value, code, &mut result, &mut buffer),
+            }
+        }
+
+        flush(&mut result, &mut buffer);
+        map.push(input, &result, source_start .. input.len(), output_start);
+
+        (result, map)
+    }
+}
+
+impl OffsetMap
+{
+    /// участки исходной строки и результата
+    pub fn segments(&self) -> &[OffsetSegment]
+    {
+        &self.segments
+    }
+
+    /// смещение результата, соответствующее смещению исходной строки; смещение внутри
+    /// изменённого участка соответствует его началу
+    pub fn to_output(&self, source_offset: usize) -> usize
+    {
+        self.map(source_offset, false, false)
+    }
+
+    /// смещение исходной строки, соответствующее смещению результата; смещение внутри
+    /// изменённого участка соответствует его началу
+    pub fn to_source(&self, output_offset: usize) -> usize
+    {
+        self.map(output_offset, true, false)
+    }
+
+    /// диапазон результата, соответствующий диапазону исходной строки: затронутые изменённые
+    /// участки входят в него целиком
+    pub fn output_range(&self, source: Range<usize>) -> Range<usize>
+    {
+        self.map(source.start, false, false) .. self.map(source.end, false, true)
+    }
+
+    /// диапазон исходной строки, соответствующий диапазону результата: затронутые изменённые
+    /// участки входят в него целиком
+    pub fn source_range(&self, output: Range<usize>) -> Range<usize>
+    {
+        self.map(output.start, true, false) .. self.map(output.end, true, true)
+    }
+
+    /// отображение смещения; reverse - из результата в исходную строку, ceil - смещение внутри
+    /// изменённого участка соответствует его концу
+    fn map(&self, offset: usize, reverse: bool, ceil: bool) -> usize
+    {
+        let ranges = |segment: &OffsetSegment| match reverse {
+            true => (segment.output.clone(), segment.source.clone()),
+            false => (segment.source.clone(), segment.output.clone()),
+        };
+
+        // первый участок, заканчивающийся после смещения
+        let index = self
+            .segments
+            .partition_point(|segment| ranges(segment).0.end <= offset);
+
+        let segment = match self.segments.get(index) {
+            Some(segment) => segment,
+            None => {
+                return self
+                    .segments
+                    .last()
+                    .map(|segment| ranges(segment).1.end)
+                    .unwrap_or(0)
+            }
+        };
+
+        let (from, to) = ranges(segment);
+
+        match (segment.changed, offset <= from.start) {
+            (_, true) => to.start,
+            (false, false) => to.start + (offset - from.start),
+            (true, false) => match ceil {
+                true => to.end,
+                false => to.start,
+            },
+        }
+    }
+
+    /// добавить участок, закончившийся в результате на его текущей длине; неизменённые
+    /// участки объединяются
+    fn push(&mut self, input: &str, result: &str, source: Range<usize>, output_start: usize)
+    {
+        if source.is_empty() && output_start == result.len() {
+            return;
+        }
+
+        let output = output_start .. result.len();
+        let changed = input.as_bytes()[source.clone()] != result.as_bytes()[output.clone()];
+
+        if let Some(last) = self.segments.last_mut() {
+            if !changed && !last.changed {
+                last.source.end = source.end;
+                last.output.end = output.end;
+
+                return;
+            }
+        }
+
+        self.segments.push(OffsetSegment {
+            source,
+            output,
+            changed,
+        });
+    }
+}
@@ -0,0 +1,82 @@
+use crate::codepoint::{CCC_VALUES, DECOMPOSING_NONSTARTERS};
+use crate::decomposed::DecomposedBuffer;
+use crate::DecomposingNormalizer;
+use crate::{
+    MARKER_COMBINES_BACKWARDS, MARKER_EXPANSION, MARKER_EXPANSION_COMBINED_EMPTY,
+    MARKER_EXPANSION_COMBINED_PATCH, MARKER_HANGUL, MARKER_NONSTARTER, MARKER_SINGLETON,
+    MARKER_STARTER,
+};
+
+/// вид декомпозиции символа, как он записан в данных нормализатора
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecompositionKind
+{
+    /// стартер без декомпозиции
+    Starter,
+    /// стартер без декомпозиции, комбинирующийся с предыдущим кодпоинтом (в т.ч. V, T чамо хангыль)
+    CombinesBackwards,
+    /// нестартер без декомпозиции
+    Nonstarter,
+    /// декомпозиция в один стартер
+    Singleton,
+    /// декомпозиция в пару кодпоинтов
+    Pair,
+    /// декомпозиция, вынесенная во внешний блок
+    Expansion,
+    /// слог хангыль, раскладывается алгоритмически
+    Hangul,
+}
+
+impl DecomposingNormalizer
+{
+    /// класс канонического комбинирования (Canonical_Combining_Class) символа
+    ///
+    /// CCC символов без декомпозиции берётся из данных нормализатора. для символов
+    /// с декомпозицией данные CCC не содержат: CCC нестартеров из DECOMPOSING_NONSTARTERS
+    /// (U+0340, U+0341, U+0343, U+0344) - CCC первого кодпоинта их декомпозиции в данных,
+    /// остальные считаются стартерами
+    #[inline]
+    pub fn ccc(&self, c: char) -> u8
+    {
+        let code = c as u32;
+        let value = self.get_decomposition_value(code);
+
+        if (value as u8) >> 1 == MARKER_NONSTARTER {
+            return CCC_VALUES[(value >> 8) as u8 as usize];
+        }
+
+        // нестартеры с декомпозицией записаны в данных как декомпозиции; остальные символы,
+        // декомпозиция которых начинается с нестартера (U+0F73, U+0F75, U+0F81 и совместимые
+        // декомпозиции) - стартеры
+        if !DECOMPOSING_NONSTARTERS.contains(&code) {
+            return 0;
+        }
+
+        let mut buffer = DecomposedBuffer::new();
+        self.decompose_to_buffer(value, code, &mut buffer);
+
+        buffer
+            .codepoints()
+            .first()
+            .map_or(0, |codepoint| codepoint.ccc())
+    }
+
+    /// вид декомпозиции символа
+    #[inline]
+    pub fn decomposition_kind(&self, c: char) -> DecompositionKind
+    {
+        let value = self.get_decomposition_value(c as u32);
+
+        match (value as u8) >> 1 {
+            MARKER_STARTER => DecompositionKind::Starter,
+            MARKER_COMBINES_BACKWARDS => DecompositionKind::CombinesBackwards,
+            MARKER_NONSTARTER => DecompositionKind::Nonstarter,
+            MARKER_SINGLETON => DecompositionKind::Singleton,
+            MARKER_EXPANSION
+            | MARKER_EXPANSION_COMBINED_PATCH
+            | MARKER_EXPANSION_COMBINED_EMPTY => DecompositionKind::Expansion,
+            MARKER_HANGUL => DecompositionKind::Hangul,
+            _ => DecompositionKind::Pair,
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// приёмник UTF-8 результата нормализации
+pub trait DecompositionSink
+{
+    /// дописать строку
+    fn write_str(&mut self, string: &str);
+
+    /// дописать символ
+    fn write_char(&mut self, c: char);
+}
+
+impl DecompositionSink for String
+{
+    #[inline(always)]
+    fn write_str(&mut self, string: &str)
+    {
+        self.push_str(string);
+    }
+
+    #[inline(always)]
+    fn write_char(&mut self, c: char)
+    {
+        self.push(c);
+    }
+}
+
+impl DecompositionSink for Vec<u8>
+{
+    #[inline(always)]
+    fn write_str(&mut self, string: &str)
+    {
+        self.extend_from_slice(string.as_bytes());
+    }
+
+    #[inline(always)]
+    fn write_char(&mut self, c: char)
+    {
+        self.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+}
+
+impl<S: DecompositionSink + ?Sized> DecompositionSink for &mut S
+{
+    #[inline(always)]
+    fn write_str(&mut self, string: &str)
+    {
+        (**self).write_str(string);
+    }
+
+    #[inline(always)]
+    fn write_char(&mut self, c: char)
+    {
+        (**self).write_char(c);
+    }
+}
+
+/// приёмник, подсчитывающий длину результата в байтах без его записи
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CountingSink
+{
+    /// длина результата в байтах
+    pub len: usize,
+}
+
+impl CountingSink
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+}
+
+impl DecompositionSink for CountingSink
+{
+    #[inline(always)]
+    fn write_str(&mut self, string: &str)
+    {
+        self.len += string.len();
+    }
+
+    #[inline(always)]
+    fn write_char(&mut self, c: char)
+    {
+        self.len += c.len_utf8();
+    }
+}
+
+/// приёмник, дописывающий результат в fmt::Write. первая ошибка записи сохраняется,
+/// последующие данные пропускаются
+#[derive(Debug)]
+pub struct FmtSink<W>
+{
+    inner: W,
+    result: fmt::Result,
+}
+
+impl<W: fmt::Write> FmtSink<W>
+{
+    pub fn new(inner: W) -> Self
+    {
+        Self {
+            inner,
+            result: Ok(()),
+        }
+    }
+
+    /// результат записи: ошибка, если хотя бы одна запись завершилась неудачей
+    pub fn result(&self) -> fmt::Result
+    {
+        self.result
+    }
+
+    /// получить приёмник fmt::Write
+    pub fn into_inner(self) -> W
+    {
+        self.inner
+    }
+}
+
+impl<W: fmt::Write> DecompositionSink for FmtSink<W>
+{
+    #[inline(always)]
+    fn write_str(&mut self, string: &str)
+    {
+        if self.result.is_ok() {
+            self.result = self.inner.write_str(string);
+        }
+    }
+
+    #[inline(always)]
+    fn write_char(&mut self, c: char)
+    {
+        if self.result.is_ok() {
+            self.result = self.inner.write_char(c);
+        }
+    }
+}
+
+/// запись в срез байт: данные, не поместившиеся в срез, только подсчитываются. после первой
+/// не поместившейся записи длина превышает размер среза, и запись в срез прекращается
+pub(crate) struct SliceOutput<'a>
+{
+    pub output: &'a mut [u8],
+    pub len: usize,
+}
+
+impl<'a> DecompositionSink for SliceOutput<'a>
+{
+    #[inline(always)]
+    fn write_str(&mut self, string: &str)
+    {
+        let end = self.len + string.len();
+
+        if let Some(slot) = self.output.get_mut(self.len .. end) {
+            slot.copy_from_slice(string.as_bytes());
+        }
+
+        self.len = end;
+    }
+
+    #[inline(always)]
+    fn write_char(&mut self, c: char)
+    {
+        self.write_str(c.encode_utf8(&mut [0; 4]));
+    }
+}
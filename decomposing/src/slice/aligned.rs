@@ -1,47 +1,113 @@
-extern crate alloc;
-
-use alloc::alloc::{alloc, dealloc};
+use alloc::alloc::{alloc, dealloc, handle_alloc_error};
 use core::alloc::Layout;
-use core::mem::size_of;
 use core::ops::Deref;
 use core::ptr::copy_nonoverlapping;
 
-/// выровненный слайс
+/// выровненный слайс: либо скопированный в выровненный блок памяти, либо ссылка на
+/// выровненный статический массив
 #[repr(align(16))]
 pub struct Aligned<'a, T>
 {
     data: &'a [T],
+    /// память выделена нами и освобождается при удалении
+    owned: bool,
+}
+
+/// массив, выровненный по 16 байт, - для статических таблиц
+#[repr(C, align(16))]
+pub struct AlignedArray<T, const N: usize>(pub [T; N]);
+
+impl<T: Copy, const N: usize> AlignedArray<T, N>
+{
+    /// скопировать слайс в массив на этапе компиляции
+    pub const fn from_slice(source: &[T]) -> Self
+    {
+        assert!(source.len() == N);
+
+        let mut array = [source[0]; N];
+        let mut i = 1;
+
+        while i < N {
+            array[i] = source[i];
+            i += 1;
+        }
+
+        Self(array)
+    }
 }
 
 impl<'a, T> Aligned<'a, T>
 {
+    /// использовать данные без копирования; они не освобождаются при удалении
+    #[inline(always)]
+    pub const fn borrowed(data: &'a [T]) -> Self
+    {
+        Self { data, owned: false }
+    }
+
     /// аллоцировать память в выровненном блоке и переместить туда данные
     #[inline(never)]
     pub fn from(source: &[T]) -> Self
     {
         let len = source.len();
 
+        // аллокация нулевого размера недопустима
+        if len == 0 {
+            return Self::borrowed(&[]);
+        }
+
         unsafe {
-            let data = alloc(Self::layout(len)) as *mut T;
+            let data = Self::allocate(len);
 
             copy_nonoverlapping(source as *const [T] as *const T, data, len);
 
             Self {
                 data: core::slice::from_raw_parts(data, len),
+                owned: true,
             }
         }
     }
 
-    fn layout(length: usize) -> Layout
+    /// аллоцировать выровненный блок памяти и заполнить его значениями f(0) .. f(len - 1)
+    #[inline(never)]
+    pub fn from_fn(len: usize, mut f: impl FnMut(usize) -> T) -> Self
     {
-        if size_of::<T>() >= 4 {
-            return Layout::array::<T>(length).unwrap();
+        if len == 0 {
+            return Self::borrowed(&[]);
         }
 
-        let size = size_of::<T>() * length;
-        let length = (size + 7) / 8;
+        unsafe {
+            let data = Self::allocate(len);
 
-        Layout::array::<u64>(length).unwrap()
+            for i in 0 .. len {
+                data.add(i).write(f(i));
+            }
+
+            Self {
+                data: core::slice::from_raw_parts(data, len),
+                owned: true,
+            }
+        }
+    }
+
+    /// выделить блок памяти под length элементов, выровненный по 16 байт
+    unsafe fn allocate(length: usize) -> *mut T
+    {
+        let layout = Self::layout(length);
+        let data = alloc(layout);
+
+        if data.is_null() {
+            handle_alloc_error(layout);
+        }
+
+        data as *mut T
+    }
+
+    fn layout(length: usize) -> Layout
+    {
+        Layout::array::<T>(length)
+            .and_then(|layout| layout.align_to(16))
+            .unwrap()
     }
 }
 
@@ -56,11 +122,27 @@ impl<'a, T> Deref for Aligned<'a, T>
     }
 }
 
+impl<'a, T: Copy> Clone for Aligned<'a, T>
+{
+    /// статические данные не копируются, выделенные нами - копируются в новый блок
+    fn clone(&self) -> Self
+    {
+        match self.owned {
+            true => Self::from(self.data),
+            false => Self::borrowed(self.data),
+        }
+    }
+}
+
 impl<'a, T> Drop for Aligned<'a, T>
 {
     #[inline(never)]
     fn drop(&mut self)
     {
+        if !self.owned {
+            return;
+        }
+
         unsafe {
             let layout = Self::layout(self.data.len());
             dealloc(self.data.as_ptr() as *mut u8, layout)
@@ -14,10 +14,18 @@ impl<'a> CharsIter<'a>
 {
     #[inline(always)]
     pub fn new(str: &'a str) -> Self
+    {
+        Self::from_bytes(str.as_bytes())
+    }
+
+    /// итератор по байтам, корректность UTF-8 которых не гарантирована - такие данные
+    /// читаются только с проверкой (next_nonascii_bytes_checked)
+    #[inline(always)]
+    pub fn from_bytes(bytes: &'a [u8]) -> Self
     {
         unsafe {
-            let length = str.len() as isize;
-            let ptr = str.as_ptr();
+            let length = bytes.len() as isize;
+            let ptr = bytes.as_ptr();
             let end = ptr.offset(length);
 
             Self {
@@ -51,6 +59,9 @@ impl<'a> CharsIter<'a>
     }
 
     /// прочитать байт без проверки длины оставшихся данных
+    ///
+    /// # Safety
+    /// итератор не должен быть пуст
     #[inline(always)]
     pub unsafe fn next_unchecked(&mut self) -> u8
     {
@@ -60,6 +71,10 @@ impl<'a> CharsIter<'a>
     }
 
     /// если мы знаем, что последующие байты - 2, 3, 4 байты UTF-8 - читаем их без проверок
+    ///
+    /// # Safety
+    /// x - первый байт многобайтовой последовательности UTF-8, оставшиеся байты которой следуют
+    /// за текущей позицией
     #[inline(always)]
     pub unsafe fn next_nonascii_bytes_unchecked(&mut self, x: u8) -> u32
     {
@@ -86,6 +101,79 @@ impl<'a> CharsIter<'a>
         code
     }
 
+    /// прочитать 2, 3, 4 байты последовательности UTF-8 с проверкой корректности;
+    /// в случае ошибки указатель остаётся за максимальной некорректной частью
+    /// последовательности (включая первый байт), возвращается её длина
+    #[inline(always)]
+    pub fn next_nonascii_bytes_checked(&mut self, x: u8) -> Result<u32, isize>
+    {
+        // длина последовательности и допустимый диапазон второго байта
+        let (width, low, high) = match x {
+            0xC2 ..= 0xDF => (2, 0x80, 0xBF),
+            0xE0 => (3, 0xA0, 0xBF),
+            0xE1 ..= 0xEC | 0xEE ..= 0xEF => (3, 0x80, 0xBF),
+            0xED => (3, 0x80, 0x9F),
+            0xF0 => (4, 0x90, 0xBF),
+            0xF1 ..= 0xF3 => (4, 0x80, 0xBF),
+            0xF4 => (4, 0x80, 0x8F),
+            _ => return Err(1),
+        };
+
+        let mut code = utf8_first_byte(x, width as u32);
+
+        for i in 1 .. width {
+            if self.is_empty() {
+                return Err(i);
+            }
+
+            let byte = unsafe { *self.ptr };
+
+            let valid = match i == 1 {
+                true => (low ..= high).contains(&byte),
+                false => byte & !CONT_MASK == 0x80,
+            };
+
+            if !valid {
+                return Err(i);
+            }
+
+            self.ptr = unsafe { self.ptr.add(1) };
+            code = utf8_acc_cont_byte(code, byte);
+        }
+
+        Ok(code)
+    }
+
+    /// количество оставшихся байт
+    #[inline(always)]
+    pub fn remaining(&self) -> usize
+    {
+        unsafe { self.end.offset_from(self.ptr) as usize }
+    }
+
+    /// ширина и код символа, предшествующего позиции "текущий указатель минус поправка"
+    ///
+    /// # Safety
+    /// перед этой позицией должен находиться хотя бы один символ строки
+    #[inline(always)]
+    pub unsafe fn previous_char(&self, offset: isize) -> (isize, u32)
+    {
+        unsafe { char_before(self.ptr.offset(-offset)) }
+    }
+
+    /// прочитать последний символ оставшихся данных, сдвинув конец слайса на его начало
+    ///
+    /// # Safety
+    /// итератор не должен быть пуст
+    #[inline(always)]
+    pub unsafe fn next_back_unchecked(&mut self) -> u32
+    {
+        let (width, code) = unsafe { char_before(self.end) };
+        self.end = unsafe { self.end.offset(-width) };
+
+        code
+    }
+
     /// конечный участок слайса от запомненной позиции
     #[inline]
     pub fn ending_slice(&self) -> &[u8]
@@ -107,6 +195,33 @@ impl<'a> CharsIter<'a>
     }
 }
 
+/// ширина и код символа UTF-8, заканчивающегося перед указателем
+#[inline(always)]
+unsafe fn char_before(end: *const u8) -> (isize, u32)
+{
+    let mut start = unsafe { end.offset(-1) };
+
+    // пропускаем байты продолжения последовательности
+    while unsafe { *start } & !CONT_MASK == 0x80 {
+        start = unsafe { start.offset(-1) };
+    }
+
+    let width = unsafe { end.offset_from(start) };
+    let first = unsafe { *start };
+
+    if first < 0x80 {
+        return (width, first as u32);
+    }
+
+    let mut code = utf8_first_byte(first, width as u32);
+
+    for i in 1 .. width {
+        code = utf8_acc_cont_byte(code, unsafe { *start.offset(i) });
+    }
+
+    (width, code)
+}
+
 /// маска, использующаяся для получения битов значения первого байта UTF-8
 const FIRST_BYTE_VALUE_MASK: u8 = 0x7F;
 /// маска, исключащая 2 старших бита в 2, 3, 4 байтах последовательности UTF-8
@@ -1,2 +1,3 @@
 pub mod aligned;
 pub mod iter;
+pub mod utf16;
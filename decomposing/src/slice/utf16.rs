@@ -0,0 +1,105 @@
+use core::marker::PhantomData;
+use core::slice::from_raw_parts;
+
+/// итератор по кодовым единицам UTF-16, аналог CharsIter
+#[repr(align(16))]
+pub struct Utf16Iter<'a>
+{
+    ptr: *const u16,
+    end: *const u16,
+    breakpoint: *const u16,
+    _marker: PhantomData<&'a u16>,
+}
+
+impl<'a> Utf16Iter<'a>
+{
+    #[inline(always)]
+    pub fn new(units: &'a [u16]) -> Self
+    {
+        unsafe {
+            let length = units.len() as isize;
+            let ptr = units.as_ptr();
+            let end = ptr.offset(length);
+
+            Self {
+                ptr,
+                breakpoint: ptr,
+                end,
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    /// запомнить текущую позицию
+    #[inline(always)]
+    pub fn set_breakpoint(&mut self)
+    {
+        self.breakpoint = self.ptr;
+    }
+
+    /// указатель на запомненной позиции?
+    #[inline(always)]
+    pub fn at_breakpoint(&mut self, offset: isize) -> bool
+    {
+        unsafe { self.ptr.offset_from(self.breakpoint) - offset == 0 }
+    }
+
+    /// данные закончились?
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool
+    {
+        unsafe { self.end.offset_from(self.ptr) == 0 }
+    }
+
+    /// прочитать кодовую единицу без проверки длины оставшихся данных
+    ///
+    /// # Safety
+    /// итератор не должен быть пуст
+    #[inline(always)]
+    pub unsafe fn next_unchecked(&mut self) -> u16
+    {
+        let old = self.ptr;
+        self.ptr = unsafe { self.ptr.add(1) };
+        *old
+    }
+
+    /// если за старшим суррогатом следует младший - прочитать его и вернуть код символа,
+    /// иначе суррогат одиночный
+    #[inline(always)]
+    pub fn next_low_surrogate(&mut self, high: u16) -> Option<u32>
+    {
+        if self.is_empty() {
+            return None;
+        }
+
+        let low = unsafe { *self.ptr };
+
+        if !(0xDC00 ..= 0xDFFF).contains(&low) {
+            return None;
+        }
+
+        self.ptr = unsafe { self.ptr.add(1) };
+
+        Some(0x10000 + (((high as u32) - 0xD800) << 10) + ((low as u32) - 0xDC00))
+    }
+
+    /// конечный участок слайса от запомненной позиции
+    #[inline]
+    pub fn ending_slice(&self) -> &[u16]
+    {
+        unsafe {
+            let length = self.end.offset_from(self.breakpoint) as usize;
+            from_raw_parts(self.breakpoint, length)
+        }
+    }
+
+    /// слайс от запомненной позиции до текущего указателя, минус поправка
+    #[inline]
+    pub fn block_slice(&self, offset: isize) -> &[u16]
+    {
+        unsafe {
+            let length = (self.ptr.offset_from(self.breakpoint) - offset) as usize;
+            from_raw_parts(self.breakpoint, length)
+        }
+    }
+}
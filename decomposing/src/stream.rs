@@ -0,0 +1,140 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::bytes::Utf8NormalizeError;
+use crate::codepoint::Codepoint;
+use crate::{flush, DecomposingNormalizer};
+
+/// потоковая NF(K)D нормализация: строка подаётся фрагментами, результат дописывается по мере
+/// готовности и совпадает с нормализацией всей строки целиком
+///
+/// между фрагментами сохраняются нестартеры, которые ещё могут быть переупорядочены, и
+/// последовательность UTF-8, разделённая границей фрагментов
+pub struct StreamingDecomposer<'a>
+{
+    /// нормализатор
+    normalizer: &'a DecomposingNormalizer,
+    /// нестартеры, ожидающие сортировки по CCC
+    buffer: Vec<Codepoint>,
+    /// начало последовательности UTF-8, прерванной концом фрагмента
+    incomplete: [u8; 4],
+    /// длина прерванной последовательности
+    incomplete_len: usize,
+    /// смещение начала следующего фрагмента от начала потока
+    offset: usize,
+    /// первая ошибка в потоке
+    error: Option<Utf8NormalizeError>,
+}
+
+impl<'a> StreamingDecomposer<'a>
+{
+    /// потоковая нормализация с использованием данного нормализатора
+    pub fn new(normalizer: &'a DecomposingNormalizer) -> Self
+    {
+        Self {
+            normalizer,
+            buffer: Vec::with_capacity(18),
+            incomplete: [0; 4],
+            incomplete_len: 0,
+            offset: 0,
+            error: None,
+        }
+    }
+
+    /// нормализовать очередной фрагмент строки
+    ///
+    /// фрагмент, следующий за прерванной последовательностью из push_bytes, делает поток
+    /// некорректным - ошибку вернёт finish
+    pub fn push(&mut self, chunk: &str, out: &mut String)
+    {
+        let _ = self.push_bytes(chunk.as_bytes(), out);
+    }
+
+    /// нормализовать очередной фрагмент байтов UTF-8; последовательность, прерванная концом
+    /// фрагмента, дописывается следующим фрагментом. после ошибки поток не обрабатывается
+    pub fn push_bytes(&mut self, chunk: &[u8], out: &mut String) -> Result<(), Utf8NormalizeError>
+    {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let mut chunk = chunk;
+
+        // дописываем прерванную последовательность и нормализуем её отдельно
+
+        if self.incomplete_len != 0 {
+            let width = match self.incomplete[0] {
+                0xC2 ..= 0xDF => 2,
+                0xE0 ..= 0xEF => 3,
+                _ => 4,
+            };
+
+            let start = self.incomplete_len;
+            let count = (width - start).min(chunk.len());
+
+            self.incomplete[start .. start + count].copy_from_slice(&chunk[.. count]);
+            chunk = &chunk[count ..];
+
+            let base = self.offset - start;
+            let sequence = self.incomplete;
+
+            self.offset += count;
+            self.incomplete_len = self.normalize(&sequence[.. start + count], base, out)?;
+
+            if self.incomplete_len != 0 {
+                return Ok(());
+            }
+        }
+
+        let base = self.offset;
+        let tail = self.normalize(chunk, base, out)?;
+
+        self.incomplete[.. tail].copy_from_slice(&chunk[chunk.len() - tail ..]);
+        self.incomplete_len = tail;
+        self.offset += chunk.len();
+
+        Ok(())
+    }
+
+    /// завершить поток: дописать оставшиеся нестартеры. ошибка - поток не является корректной
+    /// последовательностью UTF-8; после вызова нормализатор готов к следующему потоку
+    pub fn finish(&mut self, out: &mut String) -> Result<(), Utf8NormalizeError>
+    {
+        let result = match (self.error, self.incomplete_len) {
+            (Some(error), _) => Err(error),
+            (None, 0) => Ok(()),
+            (None, length) => Err(Utf8NormalizeError::new(self.offset - length)),
+        };
+
+        if result.is_ok() {
+            flush(out, &mut self.buffer);
+        }
+
+        self.buffer.clear();
+        self.incomplete_len = 0;
+        self.offset = 0;
+        self.error = None;
+
+        result
+    }
+
+    /// нормализовать часть потока, начинающуюся со смещения base; результат - длина
+    /// последовательности, прерванной концом данных
+    #[inline(always)]
+    fn normalize(
+        &mut self,
+        input: &[u8],
+        base: usize,
+        out: &mut String,
+    ) -> Result<usize, Utf8NormalizeError>
+    {
+        self.normalizer
+            .normalize_bytes_inline(input, out, &mut self.buffer, false, false)
+            .map_err(|error| {
+                let error = Utf8NormalizeError::new(base + error.offset());
+                self.error = Some(error);
+
+                error
+            })
+    }
+}
@@ -0,0 +1,209 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::codepoint::{Codepoint, InlineBuffer, NonstarterBuffer};
+use crate::decomposed::DecomposedBuffer;
+use crate::sink::{DecompositionSink, SliceOutput};
+use crate::slice::iter::CharsIter;
+use crate::{flush, write_char, write_str, DecomposingNormalizer, NeedMore};
+use crate::{MARKER_HANGUL, MARKER_NONSTARTER};
+
+/// максимальное количество нестартеров подряд в Stream-Safe Text Format
+const MAX_NONSTARTERS: usize = 30;
+
+/// COMBINING GRAPHEME JOINER - стартер без декомпозиции, вставляемый между нестартерами
+const CGJ: u32 = 0x034F;
+
+impl DecomposingNormalizer
+{
+    /// нормализация строки, приведённой к Stream-Safe Text Format (UAX #15 §13): перед символом,
+    /// с которым последовательность нестартеров превысила бы 30, вставляется U+034F, поэтому
+    /// буфер нестартеров ограничен. нестартеры считаются по декомпозиции данных нормализатора:
+    /// для NFKD - как в UAX #15, для NFD их не больше, чем в NFKD-декомпозиции, поэтому строки
+    /// в Stream-Safe Text Format не изменяются
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize_stream_safe(&self, input: &str) -> String
+    {
+        let mut result = String::with_capacity(input.len());
+        let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+
+        self.normalize_stream_safe_inline(input, &mut result, &mut buffer);
+
+        result
+    }
+
+    /// нормализация строки в срез байт без выделения памяти, результат - длина записанной
+    /// UTF-8 строки. буфер нестартеров находится на стеке, поэтому строка приводится
+    /// к Stream-Safe Text Format - результат совпадает с normalize_stream_safe, а для строк
+    /// в этом формате - с normalize. если результат не помещается в срез - ошибка
+    /// с необходимой длиной среза, содержимое среза при этом не определено
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn normalize_into_slice(&self, input: &str, output: &mut [u8]) -> Result<usize, NeedMore>
+    {
+        let mut result = SliceOutput { output, len: 0 };
+        let mut buffer = InlineBuffer::new();
+
+        self.normalize_stream_safe_inline(input, &mut result, &mut buffer);
+
+        match result.len > result.output.len() {
+            true => Err(NeedMore {
+                required: result.len,
+            }),
+            false => Ok(result.len),
+        }
+    }
+
+    /// цикл нормализации с приведением к Stream-Safe Text Format
+    #[inline(always)]
+    fn normalize_stream_safe_inline(
+        &self,
+        input: &str,
+        result: &mut impl DecompositionSink,
+        buffer: &mut impl NonstarterBuffer,
+    )
+    {
+        let mut nonstarters = 0;
+
+        let iter = &mut CharsIter::new(input);
+
+        // если буфер не пуст, то брейкпоинт находится сразу за последним обработанным символом
+
+        loop {
+            if iter.is_empty() {
+                flush(result, buffer);
+                write_str(result, iter.ending_slice());
+
+                return;
+            }
+
+            let first = unsafe { iter.next_unchecked() };
+
+            if first < 0xC2 {
+                nonstarters = 0;
+
+                if !buffer.codepoints().is_empty() {
+                    flush(result, buffer);
+                }
+
+                continue;
+            }
+
+            let code = unsafe { iter.next_nonascii_bytes_unchecked(first) };
+            let dec_value = self.get_decomposition_value(code);
+
+            let (leading, trailing) = nonstarter_counts(self, code);
+            let overflow = nonstarters + leading > MAX_NONSTARTERS;
+
+            // после вставленного U+034F последовательность нестартеров начинается заново
+            let preceding = match overflow {
+                true => 0,
+                false => nonstarters,
+            };
+
+            nonstarters = trailing.unwrap_or(preceding + leading);
+
+            let plain = (dec_value as u8 >> 2) == 0;
+
+            if plain && !overflow {
+                if !buffer.codepoints().is_empty() {
+                    flush(result, buffer);
+                }
+
+                continue;
+            }
+
+            let width = [2, 2, 3, 4][((first >> 4) & 3) as usize];
+
+            if !iter.at_breakpoint(width) {
+                write_str(result, iter.block_slice(width));
+            }
+
+            // нестартеры считаются по тем же данным, что и раскладываются, поэтому ограниченный
+            // буфер не переполняется; проверка защищает от некорректных таблиц
+
+            if overflow || buffer.is_full() {
+                flush(result, buffer);
+            }
+
+            if overflow {
+                write_char(result, CGJ);
+            }
+
+            match plain {
+                true => write_char(result, code),
+                false => self.handle_decomposition_value(dec_value, code, result, buffer),
+            }
+
+            iter.set_breakpoint();
+        }
+    }
+
+    /// строка не содержит более 30 нестартеров подряд в декомпозиции по данным нормализатора?
+    /// для NFKD-нормализатора - проверка Stream-Safe Text Format (UAX #15 §13)
+    /// исходная строка должна являться well-formed UTF-8 строкой
+    #[inline(never)]
+    pub fn is_stream_safe(&self, input: &str) -> bool
+    {
+        let mut nonstarters = 0;
+
+        let iter = &mut CharsIter::new(input);
+
+        while !iter.is_empty() {
+            let first = unsafe { iter.next_unchecked() };
+
+            if first < 0xC2 {
+                nonstarters = 0;
+                continue;
+            }
+
+            let code = unsafe { iter.next_nonascii_bytes_unchecked(first) };
+            let (leading, trailing) = nonstarter_counts(self, code);
+
+            if nonstarters + leading > MAX_NONSTARTERS {
+                return false;
+            }
+
+            nonstarters = trailing.unwrap_or(nonstarters + leading);
+        }
+
+        true
+    }
+}
+
+/// количество нестартеров в начале и в конце декомпозиции символа; None вместо количества
+/// в конце - декомпозиция состоит только из нестартеров
+#[inline(always)]
+fn nonstarter_counts(normalizer: &DecomposingNormalizer, code: u32) -> (usize, Option<usize>)
+{
+    let value = normalizer.get_decomposition_value(code);
+
+    if (value as u8 >> 2) == 0 {
+        return (0, Some(0));
+    }
+
+    match (value as u8) >> 1 {
+        MARKER_NONSTARTER => return (1, None),
+        MARKER_HANGUL => return (0, Some(0)),
+        _ => (),
+    }
+
+    let mut scratch = DecomposedBuffer::new();
+    normalizer.decompose_to_buffer(value, code, &mut scratch);
+
+    let scratch = scratch.codepoints();
+    let leading = scratch.iter().take_while(|c| c.is_nonstarter()).count();
+
+    if leading == scratch.len() {
+        return (leading, None);
+    }
+
+    let trailing = scratch
+        .iter()
+        .rev()
+        .take_while(|c| c.is_nonstarter())
+        .count();
+
+    (leading, Some(trailing))
+}
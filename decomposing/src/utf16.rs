@@ -0,0 +1,180 @@
+use alloc::vec::Vec;
+
+use crate::codepoint::Codepoint;
+use crate::slice::utf16::Utf16Iter;
+use crate::{sort_nonstarters, DecomposingNormalizer, MARKER_NONSTARTER};
+
+/// обработка одиночных (непарных) суррогатов в UTF-16
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoneSurrogates
+{
+    /// заменить на U+FFFD
+    #[default]
+    Replace,
+    /// оставить без изменений, как стартер без декомпозиции
+    Preserve,
+}
+
+impl DecomposingNormalizer
+{
+    /// нормализация строки UTF-16, одиночные суррогаты заменяются на U+FFFD
+    #[inline(never)]
+    pub fn normalize_utf16(&self, input: &[u16]) -> Vec<u16>
+    {
+        self.normalize_utf16_with(input, LoneSurrogates::Replace)
+    }
+
+    /// нормализация строки UTF-16 с заданной обработкой одиночных суррогатов
+    #[inline(never)]
+    pub fn normalize_utf16_with(&self, input: &[u16], surrogates: LoneSurrogates) -> Vec<u16>
+    {
+        let mut result = Vec::with_capacity(input.len());
+        let mut buffer: Vec<Codepoint> = Vec::with_capacity(18);
+        let iter = &mut Utf16Iter::new(input);
+
+        // если буфер не пуст, то брейкпоинт находится сразу за последним обработанным символом
+
+        loop {
+            if iter.is_empty() {
+                flush_utf16(&mut result, &mut buffer);
+                result.extend_from_slice(iter.ending_slice());
+
+                return result;
+            }
+
+            let first = unsafe { iter.next_unchecked() };
+
+            // символы до U+00A0 не имеют декомпозиции ни в NFD, ни в NFKD
+
+            if first < 0xA0 {
+                if !buffer.is_empty() {
+                    flush_utf16(&mut result, &mut buffer);
+                }
+
+                continue;
+            }
+
+            let (code, width) = match next_code(iter, first) {
+                Some(code) => code,
+                None => {
+                    if surrogates == LoneSurrogates::Replace {
+                        flush_utf16(&mut result, &mut buffer);
+                        result.extend_from_slice(iter.block_slice(1));
+                        result.push(char::REPLACEMENT_CHARACTER as u16);
+                        iter.set_breakpoint();
+                    } else if !buffer.is_empty() {
+                        flush_utf16(&mut result, &mut buffer);
+                    }
+
+                    continue;
+                }
+            };
+
+            let dec_value = self.get_decomposition_value(code);
+
+            if (dec_value as u8 >> 2) == 0 {
+                if !buffer.is_empty() {
+                    flush_utf16(&mut result, &mut buffer);
+                }
+
+                continue;
+            }
+
+            if !iter.at_breakpoint(width) {
+                result.extend_from_slice(iter.block_slice(width));
+            }
+
+            if self.decomposition_starts_with_starter(dec_value) {
+                flush_utf16(&mut result, &mut buffer);
+            }
+
+            self.decompose_to_buffer(dec_value, code, &mut buffer);
+            iter.set_breakpoint();
+        }
+    }
+
+    /// строка UTF-16 находится в форме NF(K)D? одиночный суррогат нормализуется в U+FFFD,
+    /// поэтому строка с ним не нормализована
+    #[inline(never)]
+    pub fn is_normalized_utf16(&self, input: &[u16]) -> bool
+    {
+        self.is_normalized_utf16_with(input, LoneSurrogates::Replace)
+    }
+
+    /// строка UTF-16 находится в форме NF(K)D с учётом заданной обработки одиночных суррогатов?
+    #[inline(never)]
+    pub fn is_normalized_utf16_with(&self, input: &[u16], surrogates: LoneSurrogates) -> bool
+    {
+        let iter = &mut Utf16Iter::new(input);
+        let mut last_ccc = 0;
+
+        while !iter.is_empty() {
+            let first = unsafe { iter.next_unchecked() };
+
+            if first < 0xA0 {
+                last_ccc = 0;
+                continue;
+            }
+
+            let code = match next_code(iter, first) {
+                Some((code, _)) => code,
+                None => match surrogates {
+                    LoneSurrogates::Replace => return false,
+                    LoneSurrogates::Preserve => {
+                        last_ccc = 0;
+                        continue;
+                    }
+                },
+            };
+
+            let dec_value = self.get_decomposition_value(code);
+
+            if (dec_value as u8 >> 2) == 0 {
+                last_ccc = 0;
+                continue;
+            }
+
+            if (dec_value as u8) >> 1 != MARKER_NONSTARTER {
+                return false;
+            }
+
+            let ccc = (dec_value >> 8) as u8;
+
+            if ccc < last_ccc {
+                return false;
+            }
+
+            last_ccc = ccc;
+        }
+
+        true
+    }
+}
+
+/// код и ширина (в кодовых единицах) символа, начинающегося с данной кодовой единицы;
+/// None - одиночный суррогат
+#[inline(always)]
+fn next_code(iter: &mut Utf16Iter, first: u16) -> Option<(u32, isize)>
+{
+    match first {
+        0xD800 ..= 0xDBFF => iter.next_low_surrogate(first).map(|code| (code, 2)),
+        0xDC00 ..= 0xDFFF => None,
+        _ => Some((first as u32, 1)),
+    }
+}
+
+/// отсортировать нестартеры буфера по CCC, записать его в UTF-16 результат и освободить буфер
+#[inline(always)]
+fn flush_utf16(result: &mut Vec<u16>, buffer: &mut Vec<Codepoint>)
+{
+    if !buffer.is_empty() {
+        sort_nonstarters(buffer);
+
+        for &codepoint in buffer.iter() {
+            let mut units = [0; 2];
+            result.extend_from_slice(char::from(codepoint).encode_utf16(&mut units));
+        }
+
+        buffer.clear();
+    }
+}
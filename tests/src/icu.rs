@@ -1,5 +1,24 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, BufReader, Write};
+
+use icu_normalizer::properties::CanonicalCombiningClassMap;
+use icu_normalizer::ComposingNormalizer as icu_composing;
 use icu_normalizer::DecomposingNormalizer as icu;
+use unicode_decomposing::Codepoint;
+use unicode_decomposing::ComposingNormalizer as my_composing;
+use unicode_decomposing::DecomposeCharsExt;
 use unicode_decomposing::DecomposingNormalizer as my;
+use unicode_decomposing::DecompositionKind;
+use unicode_decomposing::LoneSurrogates;
+use unicode_decomposing::NeedMore;
+use unicode_decomposing::NormalizerScratch;
+use unicode_decomposing::OffsetSegment;
+use unicode_decomposing::StreamingDecomposer;
+use unicode_decomposing::Utf8NormalizeError;
+use unicode_decomposing::{CountingSink, DecompositionSink, FmtSink};
+use unicode_decomposing::{DataError, DataHeader, DecompositionData, UnicodeVersion};
+use unicode_decomposing::{NormalizingReader, NormalizingWriter};
 
 /// сравниваем с результатами нормализации ICU
 #[test]
@@ -36,3 +55,1351 @@ fn icu()
 
     test!((my::new_nfd(), my::new_nfkd(), "my"));
 }
+
+/// сравниваем с результатами NF(K)C нормализации ICU
+#[test]
+fn icu_composing()
+{
+    let icu_nfc = icu_composing::new_nfc();
+    let icu_nfkc = icu_composing::new_nfkc();
+
+    let nfc = my_composing::new_nfc();
+    let nfkc = my_composing::new_nfkc();
+
+    for data in crate::data::files() {
+        assert_eq!(
+            nfc.normalize(data.1.as_str()),
+            icu_nfc.normalize(data.1.as_str()),
+            "nfc,  {}",
+            data.0
+        );
+        assert_eq!(
+            nfkc.normalize(data.1.as_str()),
+            icu_nfkc.normalize(data.1.as_str()),
+            "nfkc, {}",
+            data.0
+        );
+    }
+}
+
+/// сравниваем результаты проверки нормализованности с ICU
+#[test]
+fn icu_is_normalized()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+    let icu_nfc = icu_composing::new_nfc();
+    let icu_nfkc = icu_composing::new_nfkc();
+
+    let nfd = my::new_nfd();
+    let nfkd = my::new_nfkd();
+    let nfc = my_composing::new_nfc();
+    let nfkc = my_composing::new_nfkc();
+
+    for data in crate::data::files() {
+        for line in data.1.lines() {
+            assert_eq!(
+                nfd.is_normalized(line),
+                icu_nfd.is_normalized(line),
+                "nfd,  {}",
+                data.0
+            );
+            assert_eq!(
+                nfkd.is_normalized(line),
+                icu_nfkd.is_normalized(line),
+                "nfkd, {}",
+                data.0
+            );
+            assert_eq!(
+                nfc.is_normalized(line),
+                icu_nfc.is_normalized(line),
+                "nfc,  {}",
+                data.0
+            );
+            assert_eq!(
+                nfkc.is_normalized(line),
+                icu_nfkc.is_normalized(line),
+                "nfkc, {}",
+                data.0
+            );
+        }
+    }
+}
+
+/// нормализация с заимствованием исходной строки: результат совпадает с ICU, уже нормализованная
+/// строка не копируется
+#[test]
+fn icu_cow()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    let nfd = my::new_nfd();
+    let nfkd = my::new_nfkd();
+
+    for data in crate::data::files() {
+        for (normalizer, icu_normalizer, t) in [(&nfd, &icu_nfd, "nfd"), (&nfkd, &icu_nfkd, "nfkd")]
+        {
+            let expected = icu_normalizer.normalize(data.1.as_str());
+
+            assert_eq!(
+                normalizer.normalize_cow(data.1.as_str()),
+                expected,
+                "{}, {}",
+                t,
+                data.0
+            );
+            assert_eq!(
+                normalizer.normalize_owned(data.1.clone()),
+                expected,
+                "{}, {}",
+                t,
+                data.0
+            );
+
+            assert!(
+                matches!(
+                    normalizer.normalize_cow(expected.as_str()),
+                    Cow::Borrowed(_)
+                ),
+                "{}, {}",
+                t,
+                data.0
+            );
+        }
+    }
+}
+
+/// нормализация с дописыванием в общую строку и переиспользуемым буфером нестартеров
+#[test]
+fn icu_normalize_to()
+{
+    let icu_nfd = icu::new_nfd();
+    let nfd = my::new_nfd();
+
+    let mut scratch = NormalizerScratch::new();
+    let mut result = String::new();
+    let mut result_with_scratch = String::new();
+    let mut expected = String::new();
+
+    for data in crate::data::files() {
+        for line in data.1.lines() {
+            nfd.normalize_to(line, &mut result);
+            nfd.normalize_to_with_scratch(line, &mut result_with_scratch, &mut scratch);
+            expected.push_str(icu_nfd.normalize(line).as_str());
+        }
+    }
+
+    assert_eq!(result, expected);
+    assert_eq!(result_with_scratch, expected);
+}
+
+/// ленивая нормализация: символы итератора, в прямом и обратном порядке, совпадают с результатом ICU
+#[test]
+fn icu_decompose_iter()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    let nfd = my::new_nfd();
+    let nfkd = my::new_nfkd();
+
+    for data in crate::data::files() {
+        for (normalizer, icu_normalizer, t) in [(&nfd, &icu_nfd, "nfd"), (&nfkd, &icu_nfkd, "nfkd")]
+        {
+            let expected = icu_normalizer.normalize(data.1.as_str());
+
+            let forward: String = normalizer.decompose_iter(data.1.as_str()).collect();
+            let backward: String = normalizer
+                .decompose_iter(data.1.as_str())
+                .rev()
+                .collect::<Vec<char>>()
+                .into_iter()
+                .rev()
+                .collect();
+
+            assert_eq!(forward, expected, "{}, {}", t, data.0);
+            assert_eq!(backward, expected, "{}, {}", t, data.0);
+        }
+    }
+}
+
+/// нормализация итератора символов совпадает с результатом ICU
+#[test]
+fn icu_decompose_chars()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    for data in crate::data::files() {
+        assert_eq!(
+            data.1.chars().nfd().collect::<String>(),
+            icu_nfd.normalize(data.1.as_str()),
+            "nfd,  {}",
+            data.0
+        );
+        assert_eq!(
+            data.1.chars().nfkd().collect::<String>(),
+            icu_nfkd.normalize(data.1.as_str()),
+            "nfkd, {}",
+            data.0
+        );
+    }
+}
+
+/// нормализация байтов: корректные данные - результат ICU, в некорректных - ошибка
+/// с позицией первого неверного байта или замена на U+FFFD
+#[test]
+fn icu_normalize_bytes()
+{
+    let icu_nfd = icu::new_nfd();
+    let nfd = my::new_nfd();
+
+    for data in crate::data::files() {
+        let bytes = data.1.as_bytes();
+
+        assert_eq!(
+            nfd.normalize_bytes(bytes).unwrap(),
+            icu_nfd.normalize(data.1.as_str()),
+            "{}",
+            data.0
+        );
+
+        // портим данные: вставляем байт продолжения и обрезаем многобайтовую последовательность
+        let mut corrupted = bytes.to_vec();
+        corrupted.insert(corrupted.len() / 2, 0x80);
+
+        if let Some(position) = corrupted.iter().rposition(|&byte| byte >= 0xC2) {
+            corrupted.truncate(position + 1);
+        }
+
+        let expected = std::str::from_utf8(&corrupted).unwrap_err().valid_up_to();
+
+        assert_eq!(
+            nfd.normalize_bytes(&corrupted).unwrap_err().offset(),
+            expected,
+            "{}",
+            data.0
+        );
+        assert_eq!(
+            nfd.normalize_bytes_lossy(&corrupted),
+            icu_nfd.normalize(String::from_utf8_lossy(&corrupted).as_ref()),
+            "{}",
+            data.0
+        );
+    }
+}
+
+/// нормализация UTF-16 совпадает с результатом ICU; одиночные суррогаты заменяются на U+FFFD
+/// или остаются без изменений
+#[test]
+fn icu_normalize_utf16()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    let nfd = my::new_nfd();
+    let nfkd = my::new_nfkd();
+
+    for data in crate::data::files() {
+        let utf16: Vec<u16> = data.1.encode_utf16().collect();
+
+        for (normalizer, icu_normalizer, t) in [(&nfd, &icu_nfd, "nfd"), (&nfkd, &icu_nfkd, "nfkd")]
+        {
+            let expected = icu_normalizer.normalize_utf16(&utf16);
+
+            assert_eq!(
+                normalizer.normalize_utf16(&utf16),
+                expected,
+                "{}, {}",
+                t,
+                data.0
+            );
+            assert!(
+                normalizer.is_normalized_utf16(&expected),
+                "{}, {}",
+                t,
+                data.0
+            );
+            assert_eq!(
+                normalizer.is_normalized_utf16(&utf16),
+                icu_normalizer.is_normalized_utf16(&utf16),
+                "{}, {}",
+                t,
+                data.0
+            );
+
+            // одиночный суррогат в середине строки
+            let mut lone = utf16.clone();
+            lone.insert(lone.len() / 2, 0xDC00);
+
+            assert_eq!(
+                normalizer.normalize_utf16(&lone),
+                icu_normalizer.normalize_utf16(&lone),
+                "{}, {}",
+                t,
+                data.0
+            );
+            assert!(!normalizer.is_normalized_utf16(&lone), "{}, {}", t, data.0);
+
+            let preserved = normalizer.normalize_utf16_with(&lone, LoneSurrogates::Preserve);
+
+            assert!(preserved.contains(&0xDC00), "{}, {}", t, data.0);
+            assert!(
+                normalizer.is_normalized_utf16_with(&preserved, LoneSurrogates::Preserve),
+                "{}, {}",
+                t,
+                data.0
+            );
+        }
+    }
+}
+
+/// потоковая нормализация при любом разбиении на фрагменты совпадает с результатом ICU
+#[test]
+fn icu_streaming()
+{
+    let icu_nfkd = icu::new_nfkd();
+    let nfkd = my::new_nfkd();
+    let mut stream = StreamingDecomposer::new(&nfkd);
+
+    for data in crate::data::files() {
+        let expected = icu_nfkd.normalize(data.1.as_str());
+        let bytes = data.1.as_bytes();
+
+        // фрагменты разной длины: границы проходят внутри последовательностей UTF-8
+        // и последовательностей нестартеров
+        for size in [1, 2, 3, 5, 7, 64] {
+            let mut result = String::new();
+
+            for chunk in bytes.chunks(size) {
+                stream.push_bytes(chunk, &mut result).unwrap();
+            }
+
+            stream.finish(&mut result).unwrap();
+
+            assert_eq!(result, expected, "{} {}", data.0, size);
+        }
+
+        let mut result = String::new();
+
+        for line in data.1.split_inclusive('\n') {
+            stream.push(line, &mut result);
+        }
+
+        stream.finish(&mut result).unwrap();
+
+        assert_eq!(result, expected, "{}", data.0);
+
+        // поток, оборванный внутри последовательности
+        if let Some(position) = bytes.iter().rposition(|&byte| byte >= 0xC2) {
+            let mut result = String::new();
+
+            stream
+                .push_bytes(&bytes[.. position + 1], &mut result)
+                .unwrap();
+
+            assert_eq!(
+                stream.finish(&mut result).unwrap_err().offset(),
+                position,
+                "{}",
+                data.0
+            );
+        }
+    }
+}
+
+/// нормализация потоков чтения и записи совпадает с результатом ICU, некорректный UTF-8 - ошибка
+#[test]
+fn icu_io()
+{
+    let icu_nfd = icu::new_nfd();
+
+    for data in crate::data::files() {
+        let expected = icu_nfd.normalize(data.1.as_str());
+        let bytes = data.1.as_bytes();
+
+        // маленький буфер источника: границы проходят внутри последовательностей UTF-8
+        let mut reader = NormalizingReader::nfd(BufReader::with_capacity(7, bytes));
+        let mut result = Vec::new();
+
+        io::copy(&mut reader, &mut result).unwrap();
+
+        assert_eq!(result, expected.as_bytes(), "{}", data.0);
+
+        let mut writer = NormalizingWriter::nfd(Vec::new());
+
+        for chunk in bytes.chunks(5) {
+            writer.write_all(chunk).unwrap();
+        }
+
+        writer.finish().unwrap();
+
+        assert_eq!(writer.into_inner(), expected.as_bytes(), "{}", data.0);
+
+        // некорректный UTF-8: ошибка содержит смещение от начала потока
+        let mut corrupted = bytes.to_vec();
+        corrupted.insert(corrupted.len() / 2, 0xFF);
+
+        let expected = std::str::from_utf8(&corrupted).unwrap_err().valid_up_to();
+
+        let mut reader = NormalizingReader::nfd(BufReader::with_capacity(7, corrupted.as_slice()));
+        let error = io::copy(&mut reader, &mut io::sink()).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", data.0);
+        assert_eq!(
+            error
+                .get_ref()
+                .and_then(|error| error.downcast_ref::<Utf8NormalizeError>())
+                .map(|error| error.offset()),
+            Some(expected),
+            "{}",
+            data.0
+        );
+    }
+
+    // без finish задержанные нестартеры записываются при удалении
+    let mut result = Vec::new();
+
+    {
+        let mut writer = NormalizingWriter::nfd(&mut result);
+
+        writer.write_all("a\u{0301}\u{0316}".as_bytes()).unwrap();
+        writer.flush().unwrap();
+    }
+
+    assert_eq!(result, "a\u{0316}\u{0301}".as_bytes());
+
+    // ошибки приёмника: непереданные данные сохраняются, повторные вызовы не дублируют запись
+    let input = "\u{00C5}bc\u{00E9}\u{0316}";
+    let expected = icu_nfd.normalize(input);
+
+    for failures in 1 .. 4 {
+        let mut writer = NormalizingWriter::nfd(FlakyWriter {
+            data: Vec::new(),
+            failures,
+        });
+
+        writer.write_all(input.as_bytes()).unwrap();
+
+        while writer.finish().is_err() {}
+
+        assert_eq!(
+            writer.into_inner().data,
+            expected.as_bytes(),
+            "{}",
+            failures
+        );
+    }
+}
+
+/// приёмник, первые записи в который завершаются ошибкой
+struct FlakyWriter
+{
+    data: Vec<u8>,
+    failures: usize,
+}
+
+impl Write for FlakyWriter
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+    {
+        if self.failures > 0 {
+            self.failures -= 1;
+            return Err(io::Error::other("flaky"));
+        }
+
+        self.data.extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()>
+    {
+        Ok(())
+    }
+}
+
+/// нормализация с приведением к Stream-Safe Text Format, вставка U+034F
+#[test]
+fn icu_stream_safe()
+{
+    let icu_nfd = icu::new_nfd();
+    let nfd = my::new_nfd();
+    let nfkd = my::new_nfkd();
+
+    for data in crate::data::files() {
+        assert!(nfkd.is_stream_safe(data.1.as_str()), "{}", data.0);
+        assert_eq!(
+            nfd.normalize_stream_safe(data.1.as_str()),
+            icu_nfd.normalize(data.1.as_str()),
+            "{}",
+            data.0
+        );
+    }
+
+    // 30 нестартеров подряд допустимы, перед 31-м вставляется U+034F; U+0344 раскладывается
+    // в два нестартера, U+0F73 - тоже, U+1FC1 - пробел и два нестартера
+    let marks: String = "\u{0316}\u{0300}".repeat(14);
+
+    for (tail, safe) in [
+        ("\u{0301}\u{0301}", true),
+        ("\u{0301}\u{0301}\u{0301}", false),
+        ("\u{0344}", true),
+        ("\u{0301}\u{0344}", false),
+        ("\u{0F73}\u{0301}", false),
+        ("\u{1FC1}\u{0301}", true),
+    ] {
+        let input = format!("a{}{}", marks, tail);
+
+        assert_eq!(nfkd.is_stream_safe(&input), safe, "{:?}", tail);
+
+        let normalized = nfd.normalize_stream_safe(&input);
+
+        assert!(nfkd.is_stream_safe(&normalized), "{:?}", tail);
+        assert_eq!(normalized.contains('\u{034F}'), !safe, "{:?}", tail);
+
+        if safe {
+            assert_eq!(normalized, icu_nfd.normalize(&input), "{:?}", tail);
+        }
+    }
+
+    // нестартеры считаются по данным нормализатора: U+FF9E - нестартер только в NFKD
+    let input = format!("a{}\u{0301}\u{0301}\u{FF9E}", marks);
+
+    assert!(!nfkd.is_stream_safe(&input));
+    assert!(nfd.is_stream_safe(&input));
+    assert_eq!(nfd.normalize_stream_safe(&input), icu_nfd.normalize(&input));
+    assert!(nfkd.normalize_stream_safe(&input).contains('\u{034F}'));
+}
+
+/// CCC символов сравниваем с ICU, вид декомпозиции - на примерах
+#[test]
+fn icu_properties()
+{
+    let icu_ccc = CanonicalCombiningClassMap::new();
+    let nfd = my::new_nfd();
+    let nfkd = my::new_nfkd();
+
+    for c in (0 .. 0x110000).filter_map(char::from_u32) {
+        let ccc = icu_ccc.get(c).0;
+
+        assert_eq!(nfd.ccc(c), ccc, "U+{:04X}", c as u32);
+        assert_eq!(nfkd.ccc(c), ccc, "U+{:04X}", c as u32);
+    }
+
+    for (c, nfd_kind, nfkd_kind) in [
+        ('a', DecompositionKind::Starter, DecompositionKind::Starter),
+        (
+            '\u{1161}',
+            DecompositionKind::CombinesBackwards,
+            DecompositionKind::CombinesBackwards,
+        ),
+        (
+            '\u{0301}',
+            DecompositionKind::Nonstarter,
+            DecompositionKind::Nonstarter,
+        ),
+        (
+            '\u{2126}',
+            DecompositionKind::Singleton,
+            DecompositionKind::Singleton,
+        ),
+        (
+            '\u{00A0}',
+            DecompositionKind::Starter,
+            DecompositionKind::Singleton,
+        ),
+        ('\u{00C0}', DecompositionKind::Pair, DecompositionKind::Pair),
+        (
+            '\u{1E09}',
+            DecompositionKind::Expansion,
+            DecompositionKind::Expansion,
+        ),
+        (
+            '\u{3300}',
+            DecompositionKind::Starter,
+            DecompositionKind::Expansion,
+        ),
+        (
+            '\u{AC00}',
+            DecompositionKind::Hangul,
+            DecompositionKind::Hangul,
+        ),
+    ] {
+        assert_eq!(nfd.decomposition_kind(c), nfd_kind, "U+{:04X}", c as u32);
+        assert_eq!(nfkd.decomposition_kind(c), nfkd_kind, "U+{:04X}", c as u32);
+    }
+}
+
+/// декомпозиция отдельного символа совпадает с результатом ICU
+#[test]
+fn icu_decompose_char()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+    let nfd = my::new_nfd();
+    let nfkd = my::new_nfkd();
+
+    for c in (0 .. 0x110000).filter_map(char::from_u32) {
+        let string = c.to_string();
+
+        assert_eq!(
+            nfd.decompose_char(c).iter().collect::<String>(),
+            icu_nfd.normalize(&string),
+            "U+{:04X}",
+            c as u32
+        );
+        assert_eq!(
+            nfkd.decompose_char(c).iter().collect::<String>(),
+            icu_nfkd.normalize(&string),
+            "U+{:04X}",
+            c as u32
+        );
+    }
+}
+
+/// нормализация в кодпоинты: символы и CCC совпадают с ICU, срез недостаточной длины - ошибка
+#[test]
+fn icu_normalize_to_codepoints()
+{
+    let icu_ccc = CanonicalCombiningClassMap::new();
+    let icu_nfkd = icu::new_nfkd();
+    let nfkd = my::new_nfkd();
+
+    for data in crate::data::files() {
+        let codepoints = nfkd.normalize_to_codepoints(data.1.as_str());
+
+        assert_eq!(
+            codepoints.iter().map(Codepoint::char).collect::<String>(),
+            icu_nfkd.normalize(data.1.as_str()),
+            "{}",
+            data.0
+        );
+
+        for codepoint in codepoints.iter() {
+            assert_eq!(
+                codepoint.ccc(),
+                icu_ccc.get(codepoint.char()).0,
+                "{}",
+                data.0
+            );
+        }
+
+        let mut slice = vec![Codepoint::default(); codepoints.len()];
+
+        assert_eq!(
+            nfkd.normalize_to_codepoints_slice(data.1.as_str(), &mut slice),
+            Ok(codepoints.len()),
+            "{}",
+            data.0
+        );
+        assert_eq!(slice, codepoints, "{}", data.0);
+
+        if !codepoints.is_empty() {
+            assert_eq!(
+                nfkd.normalize_to_codepoints_slice(
+                    data.1.as_str(),
+                    &mut slice[.. codepoints.len() - 1]
+                ),
+                Err(NeedMore {
+                    required: codepoints.len()
+                }),
+                "{}",
+                data.0
+            );
+        }
+    }
+
+    let acute = Codepoint::new('\u{0301}', 230).unwrap();
+
+    assert_eq!((acute.char(), acute.ccc()), ('\u{0301}', 230));
+    assert_eq!(Codepoint::new('a', 0).map(|c| c.is_starter()), Some(true));
+    assert_eq!(Codepoint::new('\u{0301}', 2), None);
+}
+
+/// загрузка таблиц из бинарного формата и ошибки повреждённых данных
+#[test]
+fn icu_from_bytes()
+{
+    let icu_nfkd = icu::new_nfkd();
+    let version = UnicodeVersion::new(15, 1, 0);
+    let bytes = my::new_nfkd().to_bytes().unwrap();
+
+    assert_eq!(DataHeader::read(&bytes).unwrap().unicode_version, version);
+
+    let nfkd = my::from_bytes(&bytes).unwrap();
+
+    // версия Unicode сохраняется при загрузке и повторной записи
+    assert_eq!(nfkd.unicode_version(), Some(version));
+    assert_eq!(nfkd.to_bytes(), Ok(bytes.clone()));
+
+    // встроенные таблицы подготовлены по Unicode 15.1, как и данные ICU
+    assert_eq!(my::nfd().unicode_version(), Some(version));
+
+    let other = UnicodeVersion::new(16, 0, 0);
+    let bytes_16 = my::new_nfkd()
+        .with_unicode_version(other)
+        .to_bytes()
+        .unwrap();
+
+    assert_eq!(
+        my::from_bytes(&bytes_16).unwrap().unicode_version(),
+        Some(other)
+    );
+
+    for data in crate::data::files() {
+        assert_eq!(
+            nfkd.normalize(data.1.as_str()),
+            icu_nfkd.normalize(data.1.as_str()),
+            "{}",
+            data.0
+        );
+    }
+
+    let mut corrupted = bytes.clone();
+    corrupted[bytes.len() / 2] ^= 1;
+
+    assert_eq!(
+        my::from_bytes(&corrupted).err(),
+        Some(DataError::ChecksumMismatch)
+    );
+
+    let mut corrupted = bytes.clone();
+    corrupted[0] = b'X';
+
+    assert_eq!(
+        my::from_bytes(&corrupted).err(),
+        Some(DataError::InvalidMagic)
+    );
+
+    let mut corrupted = bytes.clone();
+    corrupted[8] = 2;
+
+    assert_eq!(
+        my::from_bytes(&corrupted).err(),
+        Some(DataError::UnsupportedFormat(2))
+    );
+
+    assert_eq!(
+        my::from_bytes(&bytes[.. bytes.len() - 1]).err(),
+        Some(DataError::Truncated)
+    );
+    assert_eq!(
+        my::from_bytes(&bytes[.. 16]).err(),
+        Some(DataError::Truncated)
+    );
+
+    let mut extended = bytes.clone();
+    extended.push(0);
+
+    assert_eq!(
+        my::from_bytes(&extended).err(),
+        Some(DataError::TrailingBytes)
+    );
+}
+
+/// проверка структуры данных нормализатора на минимальных таблицах
+#[test]
+fn validate_data()
+{
+    // минимальные таблицы: все блоки указывают на блок данных из стартеров
+    let mut index = vec![0x600u16; 0x600];
+    index.extend_from_slice(&[0; 16]);
+
+    let data = [0u32; 8];
+    let expansions = [0x4100u32, 0x30133];
+
+    let tables = |index: &[u16], data: &[u32], continuous_block_end: u32| {
+        let data = DecompositionData {
+            index,
+            data,
+            expansions: &expansions,
+            continuous_block_end,
+        };
+
+        data.validate()
+    };
+
+    assert_eq!(tables(&index, &data, 0x7F), Ok(()));
+
+    let minimal = my::try_from_baked(DecompositionData {
+        index: &index,
+        data: &data,
+        expansions: &expansions,
+        continuous_block_end: 0x7F,
+    })
+    .unwrap();
+
+    assert_eq!(minimal.normalize("abc\u{00C0}"), "abc\u{00C0}");
+
+    // CCC нестартеров с декомпозицией берётся из таблиц: здесь U+0340 - стартер
+    assert_eq!(minimal.ccc('\u{0340}'), 0);
+    assert_eq!(my::nfd().ccc('\u{0340}'), 230);
+
+    // версия Unicode таблиц не указана - их нельзя записать в бинарном формате
+    assert_eq!(minimal.to_bytes(), Err(DataError::UnknownUnicodeVersion));
+    assert!(minimal
+        .with_unicode_version(UnicodeVersion::new(15, 1, 0))
+        .to_bytes()
+        .is_ok());
+
+    // первый уровень индекса не соответствует continuous_block_end
+    assert_eq!(
+        tables(&index, &data, 0xFFF),
+        Err(DataError::ContinuousBlockMismatch)
+    );
+
+    // блок данных за пределами data
+    let mut broken = index.clone();
+    broken[0x600] = 8;
+
+    assert_eq!(
+        tables(&broken, &data, 0x7F),
+        Err(DataError::IndexOutOfBounds(0))
+    );
+
+    let check = |code: u32, value: u32| {
+        let mut broken = data;
+        broken[code as usize] = value;
+
+        tables(&index, &broken, 0x7F)
+    };
+
+    // A + U+0301, U+0301 со сдвигом на дополнительный элемент
+    assert_eq!(check(1, 0x4 << 1 | 2 << 13), Ok(()));
+    assert_eq!(check(1, 0x5 << 1 | 1 << 13), Ok(()));
+    // выход за пределы expansions, last_starter за пределами декомпозиции
+    assert_eq!(
+        check(1, 0x4 << 1 | 2 << 13 | 1 << 18),
+        Err(DataError::ExpansionOutOfBounds(1))
+    );
+    assert_eq!(
+        check(1, 0x4 << 1 | 2 << 8 | 2 << 13),
+        Err(DataError::ExpansionOutOfBounds(1))
+    );
+    // декомпозиция длиннее 18 кодпоинтов не помещается в Decomposed: стартер и нестартеры
+    let mut long = vec![0x4100u32];
+    long.extend_from_slice(&[0x30133; 30]);
+
+    for (count, expected) in [(18, Ok(())), (31, Err(DataError::InvalidMapping(1)))] {
+        let mut broken = data;
+        broken[1] = 0x4 << 1 | count << 13;
+
+        let data = DecompositionData {
+            index: &index,
+            data: &broken,
+            expansions: &long,
+            continuous_block_end: 0x7F,
+        };
+
+        assert_eq!(data.validate(), expected, "{}", count);
+    }
+
+    // last_starter указывает на нестартер
+    assert_eq!(
+        check(1, 0x4 << 1 | 1 << 8 | 2 << 13),
+        Err(DataError::InvalidMapping(1))
+    );
+    // пара A + B
+    assert_eq!(check(4, 0x41 << 1 | 0x42 << 16), Ok(()));
+    // синглтон в суррогат, пара с суррогатом, хангыль вне блока слогов, CCC вне списка классов
+    assert_eq!(
+        check(3, 0x3 << 1 | 0xD800 << 8),
+        Err(DataError::InvalidMapping(3))
+    );
+    assert_eq!(
+        check(4, 0x41 << 1 | 0xDC00 << 16),
+        Err(DataError::InvalidMapping(4))
+    );
+    assert_eq!(check(5, 0x7 << 1), Err(DataError::InvalidMapping(5)));
+    assert_eq!(
+        check(6, 0x2 << 1 | 0xFF << 8),
+        Err(DataError::InvalidMapping(6))
+    );
+}
+
+/// нормализаторы в статических переменных, без копирования данных
+#[test]
+fn icu_static()
+{
+    static NFD: my = my::new_nfd();
+    static NFKD: my = my::new_nfkd();
+
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    for data in crate::data::files() {
+        let source = data.1.as_str();
+
+        assert_eq!(
+            NFD.normalize(source),
+            icu_nfd.normalize(source),
+            "nfd, {}",
+            data.0
+        );
+        assert_eq!(
+            NFKD.normalize(source),
+            icu_nfkd.normalize(source),
+            "nfkd, {}",
+            data.0
+        );
+    }
+
+    // копия статических таблиц совпадает с ними
+    for normalizer in [&NFD, &NFKD] {
+        let bytes = normalizer.to_bytes().unwrap();
+        let copy = my::from_bytes(&bytes).unwrap();
+
+        assert_eq!(copy.to_bytes(), Ok(bytes));
+    }
+}
+
+/// общие нормализаторы и их копии в нескольких потоках
+#[test]
+fn icu_shared()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    assert!(std::ptr::eq(my::nfd(), my::nfd()));
+    assert!(std::ptr::eq(my::nfkd(), my::nfkd()));
+
+    let files = crate::data::files();
+    let expected: Vec<(String, String)> = files
+        .iter()
+        .map(|data| {
+            (
+                icu_nfd.normalize(data.1.as_str()),
+                icu_nfkd.normalize(data.1.as_str()),
+            )
+        })
+        .collect();
+
+    // копия нормализатора с выделенными таблицами
+    let owned = my::from_bytes(&my::nfd().to_bytes().unwrap()).unwrap();
+
+    std::thread::scope(|scope| {
+        for thread in 0 .. 8 {
+            let files = &files;
+            let expected = &expected;
+            let owned = owned.clone();
+
+            scope.spawn(move || {
+                let nfd = match thread % 2 {
+                    0 => my::nfd().clone(),
+                    _ => owned,
+                };
+
+                for (data, (icu_nfd, icu_nfkd)) in files.iter().zip(expected) {
+                    let source = data.1.as_str();
+
+                    assert_eq!(&nfd.normalize(source), icu_nfd, "nfd, {}", data.0);
+                    assert_eq!(&my::nfd().normalize(source), icu_nfd, "nfd, {}", data.0);
+                    assert_eq!(&my::nfkd().normalize(source), icu_nfkd, "nfkd, {}", data.0);
+                }
+            });
+        }
+    });
+}
+
+/// общие NFC/NFKC-нормализаторы и их копии в нескольких потоках
+#[test]
+fn icu_composing_shared()
+{
+    let icu_nfc = icu_composing::new_nfc();
+    let icu_nfkc = icu_composing::new_nfkc();
+
+    assert!(std::ptr::eq(my_composing::nfc(), my_composing::nfc()));
+    assert!(std::ptr::eq(my_composing::nfkc(), my_composing::nfkc()));
+
+    let files = crate::data::files();
+    let expected: Vec<(String, String)> = files
+        .iter()
+        .map(|data| {
+            (
+                icu_nfc.normalize(data.1.as_str()),
+                icu_nfkc.normalize(data.1.as_str()),
+            )
+        })
+        .collect();
+
+    std::thread::scope(|scope| {
+        for thread in 0 .. 8 {
+            let files = &files;
+            let expected = &expected;
+
+            scope.spawn(move || {
+                let nfc = match thread % 2 {
+                    0 => my_composing::nfc().clone(),
+                    _ => my_composing::new_nfc(),
+                };
+
+                for (data, (icu_nfc, icu_nfkc)) in files.iter().zip(expected) {
+                    let source = data.1.as_str();
+
+                    assert_eq!(&nfc.normalize(source), icu_nfc, "nfc, {}", data.0);
+                    assert_eq!(
+                        &my_composing::nfkc().normalize(source),
+                        icu_nfkc,
+                        "nfkc, {}",
+                        data.0
+                    );
+                }
+            });
+        }
+    });
+}
+
+/// нормализация в срез байт совпадает с результатом ICU, срез недостаточной длины - ошибка
+#[test]
+fn icu_normalize_into_slice()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    for data in crate::data::files() {
+        let source = data.1.as_str();
+
+        for (normalizer, expected) in [
+            (my::nfd(), icu_nfd.normalize(source)),
+            (my::nfkd(), icu_nfkd.normalize(source)),
+        ] {
+            let mut output = vec![0; expected.len()];
+
+            assert_eq!(
+                normalizer.normalize_into_slice(source, &mut output),
+                Ok(expected.len()),
+                "{}",
+                data.0
+            );
+            assert_eq!(output, expected.as_bytes(), "{}", data.0);
+
+            let mut output = vec![0; expected.len() / 2];
+
+            assert_eq!(
+                normalizer.normalize_into_slice(source, &mut output),
+                Err(NeedMore {
+                    required: expected.len()
+                }),
+                "{}",
+                data.0
+            );
+        }
+    }
+
+    // строка не в Stream-Safe Text Format: результат совпадает с normalize_stream_safe
+    let input = format!("a{}\u{1E09}\u{0344}", "\u{0316}\u{0300}".repeat(20));
+    let expected = my::nfd().normalize_stream_safe(&input);
+    let mut output = [0; 256];
+
+    let len = my::nfd().normalize_into_slice(&input, &mut output).unwrap();
+
+    assert_eq!(&output[.. len], expected.as_bytes());
+    assert_eq!(my::nfd().normalize_into_slice("", &mut []), Ok(0));
+}
+
+/// запись результата в приёмники
+#[test]
+fn icu_sink()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    for data in crate::data::files() {
+        let source = data.1.as_str();
+
+        for (normalizer, expected) in [
+            (my::nfd(), icu_nfd.normalize(source)),
+            (my::nfkd(), icu_nfkd.normalize(source)),
+        ] {
+            let mut string = String::from("> ");
+            normalizer.normalize_with_sink(source, &mut string);
+            assert_eq!(string[2 ..], expected, "{}", data.0);
+
+            let mut bytes = Vec::new();
+            normalizer.normalize_with_sink(source, &mut bytes);
+            assert_eq!(bytes, expected.as_bytes(), "{}", data.0);
+
+            let mut sink = FmtSink::new(String::new());
+            normalizer.normalize_with_sink(source, &mut &mut sink);
+            assert_eq!(sink.result(), Ok(()));
+            assert_eq!(sink.into_inner(), expected, "{}", data.0);
+
+            let mut counter = CountingSink::new();
+            normalizer.normalize_with_sink(source, &mut counter);
+            assert_eq!(counter.len, expected.len(), "{}", data.0);
+        }
+    }
+
+    // ошибка fmt::Write сохраняется, дальнейшая запись пропускается
+    struct Limited(String);
+
+    impl fmt::Write for Limited
+    {
+        fn write_str(&mut self, s: &str) -> fmt::Result
+        {
+            match self.0.len() + s.len() > 4 {
+                true => Err(fmt::Error),
+                false => {
+                    self.0.push_str(s);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    let mut sink = FmtSink::new(Limited(String::new()));
+
+    my::nfd().normalize_with_sink("abc\u{00C0}d", &mut sink);
+    sink.write_str("e");
+
+    assert_eq!(sink.result(), Err(fmt::Error));
+    assert_eq!(sink.into_inner().0, "abcA");
+}
+
+/// длина и количество символов результата совпадают с результатом ICU
+#[test]
+fn icu_normalized_len()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    for data in crate::data::files() {
+        let source = data.1.as_str();
+
+        for (normalizer, expected) in [
+            (my::nfd(), icu_nfd.normalize(source)),
+            (my::nfkd(), icu_nfkd.normalize(source)),
+        ] {
+            assert_eq!(
+                normalizer.normalized_len(source),
+                expected.len(),
+                "{}",
+                data.0
+            );
+            assert_eq!(
+                normalizer.normalized_char_count(source),
+                expected.chars().count(),
+                "{}",
+                data.0
+            );
+        }
+    }
+
+    // хангыль: LV - 6 байт, LVT - 9 байт; U+FDFA - 18 символов в NFKD
+    for (input, nfd, nfkd) in [
+        ("\u{AC00}", (6, 2), (6, 2)),
+        ("\u{AC01}", (9, 3), (9, 3)),
+        ("\u{FDFA}", (3, 1), (33, 18)),
+        ("a\u{0344}\u{0316}", (7, 4), (7, 4)),
+    ] {
+        let counts = |normalizer: &my| {
+            (
+                normalizer.normalized_len(input),
+                normalizer.normalized_char_count(input),
+            )
+        };
+
+        assert_eq!(counts(my::nfd()), nfd, "{:?}", input);
+        assert_eq!(counts(my::nfkd()), nfkd, "{:?}", input);
+    }
+}
+
+/// нормализация при форматировании совпадает с результатом ICU
+#[test]
+fn icu_display()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    for data in crate::data::files() {
+        let source = data.1.as_str();
+
+        assert_eq!(
+            format!("{}", my::nfd().display(source)),
+            icu_nfd.normalize(source),
+            "nfd, {}",
+            data.0
+        );
+        assert_eq!(
+            my::nfkd().display(source).to_string(),
+            icu_nfkd.normalize(source),
+            "nfkd, {}",
+            data.0
+        );
+    }
+
+    let out = format!(
+        "[{}|{}]",
+        my::nfd().display("\u{00C5}"),
+        my::nfkd().display("\u{FB01}")
+    );
+
+    assert_eq!(out, "[A\u{030A}|fi]");
+
+    // ширина, выравнивание и точность применяются к нормализованной строке
+    assert_eq!(format!("[{:>8}]", my::nfd().display("ab")), "[      ab]");
+    assert_eq!(
+        format!("[{:-<4}]", my::nfd().display("\u{00C5}")),
+        "[A\u{030A}--]"
+    );
+    assert_eq!(format!("[{:.1}]", my::nfkd().display("\u{FB01}")), "[f]");
+}
+
+/// соответствие смещений исходной строки и результата нормализации
+#[test]
+fn icu_offsets()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    for data in crate::data::files() {
+        let source = data.1.as_str();
+
+        for (normalizer, expected) in [
+            (my::nfd(), icu_nfd.normalize(source)),
+            (my::nfkd(), icu_nfkd.normalize(source)),
+        ] {
+            let (result, map) = normalizer.normalize_with_offsets(source);
+
+            assert_eq!(result, expected, "{}", data.0);
+
+            // участки следуют подряд, изменённый участок нормализуется независимо
+            let mut ends = (0, 0);
+
+            for segment in map.segments() {
+                assert_eq!((segment.source.start, segment.output.start), ends);
+
+                let part = &source[segment.source.clone()];
+
+                assert_eq!(normalizer.normalize(part), result[segment.output.clone()]);
+                assert_eq!(segment.changed, !normalizer.is_normalized(part));
+
+                ends = (segment.source.end, segment.output.end);
+            }
+
+            assert_eq!(ends, (source.len(), result.len()), "{}", data.0);
+        }
+    }
+
+    // U+1E09 - c, U+0327 (202), U+0301 (230); U+0316 (220) встаёт между ними
+    let (result, map) = my::nfd().normalize_with_offsets("ab\u{1E09}\u{0316}c");
+
+    assert_eq!(result, "abc\u{0327}\u{0316}\u{0301}c");
+    assert_eq!(
+        map.segments(),
+        [
+            OffsetSegment {
+                source: 0 .. 2,
+                output: 0 .. 2,
+                changed: false
+            },
+            OffsetSegment {
+                source: 2 .. 7,
+                output: 2 .. 9,
+                changed: true
+            },
+            OffsetSegment {
+                source: 7 .. 8,
+                output: 9 .. 10,
+                changed: false
+            },
+        ]
+    );
+
+    assert_eq!(map.to_output(1), 1);
+    assert_eq!(map.to_output(5), 2);
+    assert_eq!(map.to_output(8), 10);
+    assert_eq!(map.to_source(7), 2);
+    assert_eq!(map.to_source(9), 7);
+    assert_eq!(map.output_range(5 .. 7), 2 .. 9);
+    assert_eq!(map.output_range(1 .. 8), 1 .. 10);
+    assert_eq!(map.source_range(5 .. 7), 2 .. 7);
+    assert_eq!(map.source_range(9 .. 10), 7 .. 8);
+
+    // слог хангыль, синглтон U+2126, нестартер в начале строки
+    let (result, map) = my::nfd().normalize_with_offsets("\u{0301}\u{AC01}x\u{2126}");
+
+    assert_eq!(result, "\u{0301}\u{1100}\u{1161}\u{11A8}x\u{03A9}");
+    assert_eq!(map.to_output(2), 2);
+    assert_eq!(map.output_range(2 .. 5), 2 .. 11);
+    assert_eq!(map.source_range(11 .. 12), 5 .. 6);
+    assert_eq!(map.source_range(12 .. 14), 6 .. 9);
+    assert_eq!(map.to_source(14), 9);
+
+    let (result, map) = my::nfd().normalize_with_offsets("");
+
+    assert_eq!(result, "");
+    assert!(map.segments().is_empty());
+    assert_eq!(map.to_output(0), 0);
+}
+
+/// каноническая эквивалентность и эквивалентность по совместимости
+#[test]
+fn icu_equivalence()
+{
+    let icu_nfd = icu::new_nfd();
+    let icu_nfkd = icu::new_nfkd();
+
+    for data in crate::data::files() {
+        let source = data.1.as_str();
+        let nfd = icu_nfd.normalize(source);
+        let nfkd = icu_nfkd.normalize(source);
+
+        assert!(my::canonically_equal(source, &nfd), "{}", data.0);
+        assert!(my::compatibly_equal(&nfkd, source), "{}", data.0);
+        assert_eq!(
+            my::canonically_equal(source, &nfkd),
+            nfd == nfkd,
+            "{}",
+            data.0
+        );
+
+        // различие в конце строки
+        let changed = format!("{}\u{0301}", nfd);
+
+        assert!(!my::canonically_equal(source, &changed), "{}", data.0);
+        assert!(!my::compatibly_equal(&changed, source), "{}", data.0);
+    }
+
+    for (a, b, canonical, compatible) in [
+        ("", "", true, true),
+        ("abc", "abd", false, false),
+        ("ab", "ab\u{0301}", false, false),
+        ("\u{212B}", "\u{00C5}", true, true),
+        ("x\u{00C5}", "xA\u{030A}", true, true),
+        ("e\u{0301}\u{0316}", "\u{00E9}\u{0316}", true, true),
+        ("e\u{0316}\u{0301}", "e\u{0301}\u{0316}", true, true),
+        ("e\u{0301}\u{0300}", "e\u{0300}\u{0301}", false, false),
+        ("\u{1E09}\u{0316}", "c\u{0327}\u{0316}\u{0301}", true, true),
+        ("\u{AC01}", "\u{1100}\u{1161}\u{11A8}", true, true),
+        ("\u{FB01}", "fi", false, true),
+        ("\u{00BC}", "1\u{2044}4", false, true),
+    ] {
+        assert_eq!(my::canonically_equal(a, b), canonical, "{:?} {:?}", a, b);
+        assert_eq!(my::canonically_equal(b, a), canonical, "{:?} {:?}", b, a);
+        assert_eq!(my::compatibly_equal(a, b), compatible, "{:?} {:?}", a, b);
+        assert_eq!(
+            my::nfkd().normalized_eq(b, a),
+            compatible,
+            "{:?} {:?}",
+            b,
+            a
+        );
+    }
+
+    // сегменты длиннее буфера на стеке: строки не в Stream-Safe Text Format
+    let long = "\u{0316}\u{0301}".repeat(40);
+    let sorted = format!("a{}{}", "\u{0316}".repeat(40), "\u{0301}".repeat(40));
+
+    assert!(my::canonically_equal(&format!("a{}", long), &sorted));
+    assert!(!my::canonically_equal(
+        &format!("a{}\u{0300}", long),
+        &sorted
+    ));
+    assert!(my::canonically_equal(
+        &format!("{}\u{00E1}{}", long, long),
+        &format!("{}a{}\u{0301}", long, long)
+    ));
+}
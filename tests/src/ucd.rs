@@ -1,4 +1,5 @@
 use unicode_data::{NormalizationTest, NORMALIZATION_TESTS};
+use unicode_decomposing::ComposingNormalizer as my_composing;
 use unicode_decomposing::DecomposingNormalizer as my;
 
 macro_rules! test {
@@ -67,3 +68,94 @@ fn ucd_test_nfkd()
 
     test_group!(my::new_nfkd());
 }
+
+/// тесты NFC нормализации из UCD
+#[test]
+fn ucd_test_nfc()
+{
+    // c2 ==  toNFC(c1) ==  toNFC(c2) ==  toNFC(c3)
+    // c4 ==  toNFC(c4) ==  toNFC(c5)
+
+    let tests: &Vec<NormalizationTest> = &NORMALIZATION_TESTS;
+
+    macro_rules! test_group {
+        ($($normalizer: expr),+) => {
+            $(
+                let normalizer = $normalizer;
+
+                for t in tests {
+                    test!(t.c2, t.c1, normalizer, t, "{} {}: c2 == toNFC(c1)");
+                    test!(t.c2, t.c2, normalizer, t, "{} {}: c2 == toNFC(c2)");
+                    test!(t.c2, t.c3, normalizer, t, "{} {}: c2 == toNFC(c3)");
+                    test!(t.c4, t.c4, normalizer, t, "{} {}: c4 == toNFC(c4)");
+                    test!(t.c4, t.c5, normalizer, t, "{} {}: c4 == toNFC(c5)");
+                }
+            )+
+        };
+    }
+
+    test_group!(my_composing::new_nfc());
+}
+
+/// тесты NFKC нормализации из UCD
+#[test]
+fn ucd_test_nfkc()
+{
+    // c4 == toNFKC(c1) == toNFKC(c2) == toNFKC(c3) == toNFKC(c4) == toNFKC(c5)
+
+    let tests: &Vec<NormalizationTest> = &NORMALIZATION_TESTS;
+
+    macro_rules! test_group {
+        ($($normalizer: expr),+) => {
+            $(
+            let normalizer = $normalizer;
+
+            for t in tests {
+                test!(t.c4, t.c1, normalizer, t, "{} {}: c4 == toNFKC(c1)");
+                test!(t.c4, t.c2, normalizer, t, "{} {}: c4 == toNFKC(c2)");
+                test!(t.c4, t.c3, normalizer, t, "{} {}: c4 == toNFKC(c3)");
+                test!(t.c4, t.c4, normalizer, t, "{} {}: c4 == toNFKC(c4)");
+                test!(t.c4, t.c5, normalizer, t, "{} {}: c4 == toNFKC(c5)");
+            }
+        )+
+        };
+    }
+
+    test_group!(my_composing::new_nfkc());
+}
+
+/// проверка нормализованности строк из тестов UCD: результат должен совпадать
+/// со сравнением строки с её нормализованной формой
+#[test]
+fn ucd_test_is_normalized()
+{
+    let tests: &Vec<NormalizationTest> = &NORMALIZATION_TESTS;
+
+    macro_rules! test_group {
+        ($($normalizer: expr),+) => {
+            $(
+                let normalizer = $normalizer;
+
+                for t in tests {
+                    for s in [&t.c1, &t.c2, &t.c3, &t.c4, &t.c5] {
+                        assert_eq!(
+                            normalizer.is_normalized(s),
+                            normalizer.normalize(s) == *s,
+                            "{} {}: {}",
+                            t.line,
+                            t.description,
+                            stringify!($normalizer)
+                        );
+                    }
+                }
+            )+
+        };
+    }
+
+    test_group!(
+        my::new_nfd(),
+        my::new_nfkd(),
+        my_composing::new_nfc(),
+        my_composing::new_nfkc()
+    );
+}